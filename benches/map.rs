@@ -0,0 +1,49 @@
+//! Timings for the engine on big boards. Run with `cargo bench`.
+
+extern crate minesweeper;
+
+use std::time::{Duration, Instant};
+
+use minesweeper::board::Board;
+use minesweeper::game::{Game, Preset};
+use minesweeper::topology::{Tiling, Topology};
+
+const SIDE: usize = 1000;
+/// The same density as the expert preset.
+const MINES: usize = SIDE * SIDE * 99 / (30 * 16);
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 / 1_000_000.0
+}
+
+fn time<T, F: FnOnce() -> T>(name: &str, run: F) -> T {
+    let start = Instant::now();
+    let result = run();
+    println!("  {:<28} {:>9.1} ms", name, millis(start.elapsed()));
+    result
+}
+
+fn main() {
+    let topologies = [
+        Topology::new(Tiling::Square, false),
+        Topology::new(Tiling::Hex, false),
+        Topology::new(Tiling::Triangle, false),
+        Topology::new(Tiling::Square, true),
+    ];
+    let preset = Preset::Custom { width: SIDE, height: SIDE, depth: 1, mines: MINES };
+
+    for &topology in topologies.iter() {
+        println!("{} {}x{}, {} mines", topology.name(), SIDE, SIDE, MINES);
+        let mut game = time("generate", || Game::new(preset, topology, 1));
+        time("3BV", || game.map.three_bv());
+        time("reveal every safe cell", || {
+            for coord in game.map.coords() {
+                if !game.map.is_mine(coord) {
+                    game.map.open(coord);
+                }
+            }
+        });
+        assert!(game.map.is_cleared());
+        time("player's board", || Board::from_map(&game.map));
+    }
+}
@@ -0,0 +1,40 @@
+//! The fonts and pictures the game draws with.
+//!
+//! Everything the game needs is built into the binary, so it runs from any directory. Setting
+//! `MINESWEEPER_ASSETS` to a directory laid out like the repository's `assets` overrides the
+//! bundled files with those in it, and adds any skins it holds: a file the directory doesn't have
+//! is taken from the binary, and one that can't be read is reported and taken from the binary as
+//! well.
+
+use std::env;
+use std::path::PathBuf;
+
+/// The variable naming the override directory.
+pub const ENV: &'static str = "MINESWEEPER_ASSETS";
+
+/// The font, relative to the assets directory.
+pub const FONT_PATH: &'static str = "fonts/NotoSans/NotoSans-Regular.ttf";
+
+/// The skins, relative to the assets directory.
+pub const SKINS_PATH: &'static str = "skins";
+
+/// The bundled font, Noto Sans.
+pub const FONT: &'static [u8] = include_bytes!("../assets/fonts/NotoSans/NotoSans-Regular.ttf");
+
+/// The bundled skin's name, manifest and sheet.
+pub const CLASSIC: &'static str = "classic";
+pub const CLASSIC_MANIFEST: &'static str = include_str!("../assets/skins/classic/skin.txt");
+pub const CLASSIC_SHEET: &'static [u8] = include_bytes!("../assets/skins/classic/sheet.png");
+
+/// The override directory, if `MINESWEEPER_ASSETS` names one.
+pub fn dir() -> Option<PathBuf> {
+    match env::var_os(ENV) {
+        Some(ref dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => None,
+    }
+}
+
+/// The file at `path` in the override directory, if it has one to use instead of the bundled.
+pub fn find(path: &str) -> Option<PathBuf> {
+    dir().map(|dir| dir.join(path)).and_then(|file| if file.exists() { Some(file) } else { None })
+}
@@ -0,0 +1,32 @@
+//! `leaderboard [ADDRESS] [SCORES_FILE]`
+//!
+//! Runs the speed-running leaderboard server, by default on `127.0.0.1:7878` with scores kept
+//! in `leaderboard.tsv` in the working directory.
+
+extern crate minesweeper;
+
+use std::env;
+use std::path::Path;
+use std::process;
+
+use minesweeper::leaderboard::{self, Leaderboard};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let addr = args.get(0).map(|arg| arg.as_str()).unwrap_or("127.0.0.1:7878");
+    let path = args.get(1).map(|arg| arg.as_str()).unwrap_or("leaderboard.tsv");
+
+    let mut board = match Leaderboard::open(Path::new(path)) {
+        Ok(board) => board,
+        Err(e) => {
+            println!("error: couldn't read {}: {}", path, e);
+            process::exit(1);
+        },
+    };
+
+    println!("Leaderboard listening on {} ({} scores from {})", addr, board.scores.len(), path);
+    if let Err(e) = leaderboard::serve(addr, &mut board) {
+        println!("error: {}", e);
+        process::exit(1);
+    }
+}
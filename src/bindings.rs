@@ -0,0 +1,235 @@
+//! Which mouse buttons and keys do what to a cell.
+//!
+//! A binding maps a trigger to one of the game's actions. Triggers are named as in the settings
+//! file: a mouse button, `left`, `right`, `middle` or `both` (left and right together), after any
+//! of the modifiers `shift`, `ctrl` and `alt`, as in `shift-left`; or a key acting on the cell
+//! under the mouse, as in `key-z`. The bindings start from a preset and the settings file can
+//! change any of them:
+//!
+//! ```text
+//! [bindings]
+//! preset = "classic"
+//! middle = "chord"
+//! key-space = "none"
+//! ```
+//!
+//! An unbound middle button pans the board instead.
+
+use game::Action;
+
+/// The presets, in the order the settings screen lists them.
+pub const PRESETS: [&'static str; 3] = ["classic", "left-handed", "one-button"];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+    /// Left and right held together.
+    Both,
+}
+
+impl Button {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Button::Left => "left",
+            Button::Right => "right",
+            Button::Middle => "middle",
+            Button::Both => "both",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Button> {
+        match name {
+            "left" => Some(Button::Left),
+            "right" => Some(Button::Right),
+            "middle" => Some(Button::Middle),
+            "both" => Some(Button::Both),
+            _ => None,
+        }
+    }
+}
+
+/// The modifier keys held with a button.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub fn none() -> Modifiers {
+        Default::default()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Trigger {
+    Mouse(Button, Modifiers),
+    /// A key, by its lowercase name: a letter, a digit, or a name such as `space`.
+    Key(String),
+}
+
+impl Trigger {
+    pub fn name(&self) -> String {
+        match *self {
+            Trigger::Mouse(button, modifiers) => {
+                let mut name = String::new();
+                if modifiers.shift {
+                    name.push_str("shift-");
+                }
+                if modifiers.ctrl {
+                    name.push_str("ctrl-");
+                }
+                if modifiers.alt {
+                    name.push_str("alt-");
+                }
+                name.push_str(button.name());
+                name
+            },
+            Trigger::Key(ref key) => format!("key-{}", key),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Trigger> {
+        if name.starts_with("key-") && name.len() > 4 {
+            return Some(Trigger::Key(name[4..].to_lowercase()));
+        }
+        let mut words: Vec<&str> = name.split('-').collect();
+        let button = match words.pop().and_then(Button::from_name) {
+            Some(button) => button,
+            None => return None,
+        };
+        let mut modifiers = Modifiers::none();
+        for word in words {
+            match word {
+                "shift" => modifiers.shift = true,
+                "ctrl" => modifiers.ctrl = true,
+                "alt" => modifiers.alt = true,
+                _ => return None,
+            }
+        }
+        Some(Trigger::Mouse(button, modifiers))
+    }
+}
+
+/// The bindings of a preset.
+fn preset(name: &str) -> Option<Vec<(Trigger, Action)>> {
+    let mouse = |button, modifiers| Trigger::Mouse(button, modifiers);
+    let key = |name: &str| Trigger::Key(name.to_owned());
+    let shift = Modifiers { shift: true, ..Modifiers::none() };
+    let ctrl = Modifiers { ctrl: true, ..Modifiers::none() };
+    let none = Modifiers::none();
+    // Every preset plays from the keyboard too, on the cell under the mouse.
+    let keys = vec![(key("z"), Action::Open), (key("x"), Action::Flag), (key("c"), Action::Chord)];
+    let mut bindings = match name {
+        "classic" => vec![
+            (mouse(Button::Left, none), Action::Open),
+            (mouse(Button::Right, none), Action::Flag),
+            (mouse(Button::Both, none), Action::Chord),
+        ],
+        // The buttons swapped, for a mouse in the left hand.
+        "left-handed" => vec![
+            (mouse(Button::Right, none), Action::Open),
+            (mouse(Button::Left, none), Action::Flag),
+            (mouse(Button::Both, none), Action::Chord),
+        ],
+        // For a touchpad or a single button mouse.
+        "one-button" => vec![
+            (mouse(Button::Left, none), Action::Open),
+            (mouse(Button::Left, shift), Action::Flag),
+            (mouse(Button::Left, ctrl), Action::Chord),
+        ],
+        _ => return None,
+    };
+    bindings.extend(keys);
+    Some(bindings)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    /// The preset the bindings start from.
+    pub preset: String,
+    /// Changes to the preset, in the order they were made. `None` unbinds the trigger.
+    overrides: Vec<(Trigger, Option<Action>)>,
+    /// The preset's own bindings.
+    table: Vec<(Trigger, Action)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        Bindings::preset("classic").unwrap()
+    }
+}
+
+impl Bindings {
+    /// A preset's bindings, with nothing changed.
+    pub fn preset(name: &str) -> Option<Bindings> {
+        preset(name).map(|table| {
+            Bindings {
+                preset: name.to_owned(),
+                overrides: vec![],
+                table: table,
+            }
+        })
+    }
+
+    /// Start from another preset, keeping the changes made to this one.
+    pub fn set_preset(&mut self, name: &str) -> Result<(), String> {
+        self.table = try!(preset(name).ok_or(format!("unknown bindings preset: {}", name)));
+        self.preset = name.to_owned();
+        Ok(())
+    }
+
+    /// Bind `trigger` to `action`, or unbind it.
+    pub fn set(&mut self, trigger: Trigger, action: Option<Action>) {
+        self.overrides.retain(|&(ref bound, _)| *bound != trigger);
+        self.overrides.push((trigger, action));
+    }
+
+    fn get(&self, trigger: &Trigger) -> Option<Option<Action>> {
+        self.overrides.iter().find(|&&(ref bound, _)| bound == trigger).map(|&(_, action)| action)
+            .or_else(|| {
+                self.table.iter().find(|&&(ref bound, _)| bound == trigger)
+                    .map(|&(_, action)| Some(action))
+            })
+    }
+
+    /// What a button does with these modifiers held. Modifiers without a binding of their own
+    /// are ignored.
+    pub fn mouse(&self, button: Button, modifiers: Modifiers) -> Option<Action> {
+        self.get(&Trigger::Mouse(button, modifiers))
+            .unwrap_or_else(|| {
+                self.get(&Trigger::Mouse(button, Modifiers::none())).and_then(|action| action)
+            })
+    }
+
+    /// What a key does, by its lowercase name.
+    pub fn key(&self, name: &str) -> Option<Action> {
+        self.get(&Trigger::Key(name.to_owned())).and_then(|action| action)
+    }
+
+    /// The changes to the preset as `trigger = action` pairs for the settings file, `none`
+    /// standing for unbound.
+    pub fn overrides(&self) -> Vec<(String, &'static str)> {
+        self.overrides.iter()
+            .map(|&(ref trigger, action)| {
+                (trigger.name(), action.map_or("none", |action| action.name()))
+            })
+            .collect()
+    }
+
+    /// Read one `trigger = action` pair of the settings file.
+    pub fn set_by_name(&mut self, trigger: &str, action: &str) -> Result<(), String> {
+        let trigger = try!(Trigger::from_name(trigger)
+            .ok_or(format!("unknown trigger: {}", trigger)));
+        let action = match action {
+            "none" => None,
+            name => Some(try!(Action::from_name(name)
+                .ok_or(format!("unknown action: {}", name)))),
+        };
+        self.set(trigger, action);
+        Ok(())
+    }
+}
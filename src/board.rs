@@ -0,0 +1,156 @@
+//! What a player may know about a game: the opened numbers, the flags and which cells are still
+//! unknown. Frontends, bots and anything talking to other players should look at a `Board`
+//! rather than the `Map` behind it, which knows where every mine is.
+//!
+//! The full solution is only available on purpose, through `Map::solution`.
+
+use std::fmt;
+
+use coord::{Coord, Coords, Size};
+use game::{Game, Status};
+use map::{Interaction, Map};
+use render::Renderer;
+use topology::{Neighbours, Stencil, Topology};
+
+/// One cell as the player sees it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tile {
+    Unknown,
+    Flagged,
+    /// Marked as unsure, which only the player's memory cares about.
+    Questioned,
+    /// An opened cell and the number of mines around it.
+    Number(usize),
+    /// A mine that went off or, once the game is lost, one that was never found.
+    Mine,
+}
+
+impl Tile {
+    /// A single character standing for the tile. Numbers above nine continue with letters, as
+    /// layered boards can have up to 26 neighbours.
+    pub fn symbol(&self) -> char {
+        match *self {
+            Tile::Unknown => '#',
+            Tile::Flagged => 'F',
+            Tile::Questioned => '?',
+            Tile::Number(0) => '.',
+            Tile::Number(n) if n < 36 => ::std::char::from_digit(n as u32, 36).unwrap(),
+            Tile::Number(_) => '+',
+            Tile::Mine => '*',
+        }
+    }
+}
+
+/// The player-visible state of a map.
+#[derive(Clone, Debug)]
+pub struct Board {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    /// How many mines are hidden on the board, which the player is told.
+    pub mines: usize,
+    pub topology: Topology,
+    stencil: Stencil,
+    tiles: Vec<Tile>,
+}
+
+impl Board {
+    /// What a player sees of `map` while playing.
+    pub fn from_map(map: &Map) -> Board {
+        let tiles = map.cells().map(|(_, cell)| {
+            match cell.interaction() {
+                Interaction::Undiscovered => Tile::Unknown,
+                Interaction::Flagged => Tile::Flagged,
+                Interaction::Questioned => Tile::Questioned,
+                Interaction::Opened if cell.is_mine() => Tile::Mine,
+                Interaction::Opened => Tile::Number(cell.nearby()),
+            }
+        }).collect();
+        Board {
+            width: map.width,
+            height: map.height,
+            depth: map.depth,
+            mines: map.mines(),
+            topology: map.topology,
+            stencil: map.stencil().clone(),
+            tiles: tiles,
+        }
+    }
+
+    /// What a player sees of `game`: the map while playing and, once the game is lost, where the
+    /// remaining mines were.
+    pub fn from_game(game: &Game) -> Board {
+        let mut board = Board::from_map(&game.map);
+        if game.status == Status::Failed {
+            for (tile, (_, cell)) in board.tiles.iter_mut().zip(game.map.cells()) {
+                if cell.is_mine() && (*tile == Tile::Unknown || *tile == Tile::Questioned) {
+                    *tile = Tile::Mine;
+                }
+            }
+        }
+        board
+    }
+
+    /// A single layer board of tiles given row by row, for views of boards without a `Map`
+    /// behind them. Its mine count is unknown and left at 0.
+    pub fn from_tiles(width: usize, height: usize, topology: Topology, tiles: Vec<Tile>) -> Board {
+        assert_eq!(tiles.len(), width * height);
+        let size = Size::new(width, height, 1);
+        Board {
+            width: width,
+            height: height,
+            depth: 1,
+            mines: 0,
+            topology: topology,
+            stencil: topology.stencil(size),
+            tiles: tiles,
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height, self.depth)
+    }
+
+    pub fn tile(&self, coord: Coord) -> Tile {
+        self.tiles[self.size().index(coord)]
+    }
+
+    /// Every cell's coordinates, layer by layer and row by row.
+    pub fn coords(&self) -> Coords {
+        self.size().coords()
+    }
+
+    /// The cells whose mines `coord`'s number counts.
+    pub fn neighbours(&self, coord: Coord) -> Neighbours {
+        self.stencil.neighbours(coord)
+    }
+
+    pub fn flags(&self) -> usize {
+        self.tiles.iter().filter(|&&tile| tile == Tile::Flagged).count()
+    }
+
+    /// The mine count less the flags placed, as the counter on the board shows it.
+    pub fn mines_left(&self) -> isize {
+        self.mines as isize - self.flags() as isize
+    }
+}
+
+/// The player's view, drawn by a plain `Renderer`.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Renderer::new().player(self))
+    }
+}
+
+/// Every cell of a map, mines and all, for debugging. Get one with `Map::solution`; never show
+/// it to a player.
+pub struct Solution<'a> {
+    pub map: &'a Map,
+}
+
+/// Like a `Board` with every cell opened: mines are `*`, the rest show their numbers.
+impl<'a> fmt::Display for Solution<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Renderer::new().solution(self.map))
+    }
+}
@@ -0,0 +1,591 @@
+//! The board as a single widget.
+//!
+//! `BoardView` lays a board's cells out for its tiling, draws only the cells inside its own
+//! rectangle and works out which cell the mouse is over, so a 200x200 board costs no more to
+//! update than the cells on screen. Cells are never drawn smaller than `MIN_CELL_SIZE`, so a
+//! board too big to fit shows only part of itself, and the cells on screen stay few however big
+//! the board is.
+//!
+//! Boards larger than the widget are zoomed with the mouse wheel and panned by dragging with the
+//! middle button, or with the left one while `drag_pans` is set (the GUI sets it while space is
+//! held). The widget doesn't keep the zoom and pan itself: it is given a `Viewport` and reports
+//! the one the player asked for, so that other widgets can show and change it too.
+//!
+//! Clicks are reported as the actions the player's `Bindings` give them. A middle button bound to
+//! an action clicks rather than pans.
+
+use std::cmp;
+use std::ops::Range;
+
+use conrod::{
+    Backend,
+    Color,
+    Colorable,
+    CommonBuilder,
+    Dimensions,
+    FontSize,
+    Labelable,
+    Point,
+    Rect,
+    Scalar,
+    UpdateArgs,
+    Widget,
+    WidgetKind,
+};
+
+use minesweeper::bindings::{Bindings, Button, Modifiers};
+use minesweeper::board::{Board, Tile};
+use minesweeper::coord::Coord;
+use minesweeper::game::Action;
+use minesweeper::skin::Sprite;
+use minesweeper::topology::Tiling;
+
+use cell::{self, Interaction, Shape, Slots, Style};
+use sprites::Sprites;
+use theme::Palette;
+
+/// A `&'static str` that can be used to uniquely identify our widget type.
+pub const KIND: WidgetKind = "BoardView";
+
+/// The largest cells are drawn, however far the board is zoomed in.
+pub const MAX_CELL_SIZE: Scalar = 96.0;
+/// The smallest cells are drawn. Boards that would need smaller ones to fit are cut to the
+/// widget instead.
+pub const MIN_CELL_SIZE: Scalar = 12.0;
+/// How far the board can be zoomed in from fitting the widget. It can't be zoomed out further.
+pub const MIN_ZOOM: Scalar = 1.0;
+pub const MAX_ZOOM: Scalar = 32.0;
+/// Labels smaller than this aren't drawn, as they can't be read.
+const MIN_FONT_SIZE: FontSize = 6;
+
+/// How the board is zoomed and panned inside the widget.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// How many times larger than fitting the widget the board is drawn.
+    pub zoom: Scalar,
+    /// How far the board's centre is moved from the widget's centre, in pixels.
+    pub pan: Point,
+}
+
+impl Viewport {
+    /// The whole board, as large as fits, or the middle of it if it doesn't fit with cells of
+    /// `MIN_CELL_SIZE`.
+    pub fn fit() -> Viewport {
+        Viewport {
+            zoom: 1.0,
+            pan: [0.0, 0.0],
+        }
+    }
+
+    /// Zoom by `factor`, keeping what is at `around` (relative to the widget's centre) in place.
+    pub fn zoom_by(&self, factor: Scalar, around: Point) -> Viewport {
+        let zoom = (self.zoom * factor).max(MIN_ZOOM).min(MAX_ZOOM);
+        let factor = zoom / self.zoom;
+        Viewport {
+            zoom: zoom,
+            pan: [around[0] - (around[0] - self.pan[0]) * factor,
+                  around[1] - (around[1] - self.pan[1]) * factor],
+        }
+    }
+
+    /// Move the board by `by` pixels.
+    pub fn pan_by(&self, by: Point) -> Viewport {
+        Viewport {
+            zoom: self.zoom,
+            pan: [self.pan[0] + by[0], self.pan[1] + by[1]],
+        }
+    }
+
+    /// The part of `board` shown by a widget of `dim`, as fractions of the board's width and
+    /// height from its top left corner: `[left, top, right, bottom]`.
+    pub fn window(&self, board: &Board, dim: Dimensions) -> [Scalar; 4] {
+        let (size, units_w, units_h) = Layout::cells(board, dim, *self);
+        let (w, h) = (units_w * size, units_h * size);
+        [0.5 + (-dim[0] / 2.0 - self.pan[0]) / w,
+         0.5 - (dim[1] / 2.0 - self.pan[1]) / h,
+         0.5 + (dim[0] / 2.0 - self.pan[0]) / w,
+         0.5 - (-dim[1] / 2.0 - self.pan[1]) / h]
+    }
+
+    /// Pan so that the point `fraction` of the way across and down `board` is in the middle of
+    /// a widget of `dim`.
+    pub fn centred_on(&self, board: &Board, dim: Dimensions, fraction: Point) -> Viewport {
+        let (size, units_w, units_h) = Layout::cells(board, dim, *self);
+        Viewport {
+            zoom: self.zoom,
+            pan: [(0.5 - fraction[0]) * units_w * size, (fraction[1] - 0.5) * units_h * size],
+        }
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Viewport {
+        Viewport::fit()
+    }
+}
+
+/// What the player did on the board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// Buttons bound to an action were released over the cell they went down on.
+    Click(Coord, Action),
+    /// The board was zoomed or panned.
+    View(Viewport),
+    /// Buttons are being held on a cell, or no longer are. Dragging off the cell lets go of
+    /// it, and dragging back takes it again.
+    Held(Option<(Coord, Action)>),
+    /// The mouse moved onto another cell, or off the board.
+    Hover(Option<Coord>),
+}
+
+/// One layer of a board, drawn into the widget's rectangle.
+pub struct BoardView<'a, F> {
+    common: CommonBuilder,
+    board: &'a Board,
+    layer: usize,
+    viewport: Viewport,
+    /// Whether dragging with the left button pans rather than clicks.
+    drag_pans: bool,
+    /// What the buttons do, and the modifier keys held. The classic bindings if not given.
+    maybe_bindings: Option<&'a Bindings>,
+    modifiers: Modifiers,
+    /// Whether square cells with sprites are left for `sprites::Sprites` to draw.
+    skinned: bool,
+    /// Called with every click, zoom and pan.
+    maybe_react: Option<F>,
+    style: Style,
+    enabled: bool,
+}
+
+/// Represents the unique, cached state for the board widget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    interaction: Interaction,
+    /// The cell under the mouse, if any.
+    hovered: Option<Coord>,
+    /// The cell the buttons went down on, while they are held. Only releasing them on the same
+    /// cell clicks it.
+    pressed: Option<Coord>,
+    /// Where the mouse was last update while dragging the board around.
+    maybe_drag_from: Option<Point>,
+    /// The primitives of the cells on screen, which grow with the most cells ever shown.
+    slots: Vec<Slots>,
+}
+
+/// Where the cells of a board go inside a rectangle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+    tiling: Tiling,
+    width: usize,
+    height: usize,
+    /// The top left corner of the first cell's box.
+    origin: Point,
+    /// The size of every cell's box. Hexagons and triangles reach outside their boxes to
+    /// interlock.
+    cell: Dimensions,
+}
+
+impl Layout {
+    /// Lay a board out in `rect`, zoomed and panned as `viewport` says.
+    pub fn new(board: &Board, rect: Rect, viewport: Viewport) -> Layout {
+        let (size, units_w, units_h) = Layout::cells(board, rect.dim(), viewport);
+        let (x, y) = (rect.x() + viewport.pan[0], rect.y() + viewport.pan[1]);
+        let mut origin = [x - units_w * size / 2.0, y + units_h * size / 2.0];
+        match board.topology.tiling {
+            // Triangles reach half a cell to the left of their boxes, hexagons a sixth of a row
+            // above theirs.
+            Tiling::Triangle => origin[0] += size / 2.0,
+            Tiling::Hex => origin[1] -= size / 6.0,
+            Tiling::Square => (),
+        }
+        Layout {
+            tiling: board.topology.tiling,
+            width: board.width,
+            height: board.height,
+            origin: origin,
+            cell: [size, size],
+        }
+    }
+
+    /// The size of a cell in a widget of `dim`, and how many cells wide and high the board is
+    /// drawn.
+    fn cells(board: &Board, dim: Dimensions, viewport: Viewport) -> (Scalar, Scalar, Scalar) {
+        let (units_w, units_h) = Layout::units(board.topology.tiling, board.width, board.height);
+        let fit = (dim[0] / units_w).min(dim[1] / units_h).max(MIN_CELL_SIZE);
+        ((fit * viewport.zoom).min(MAX_CELL_SIZE), units_w, units_h)
+    }
+
+    /// How many cells wide and high a board is drawn, counting the overhang of hexagons and
+    /// triangles.
+    pub fn units(tiling: Tiling, width: usize, height: usize) -> (Scalar, Scalar) {
+        let (width, height) = (width as Scalar, height as Scalar);
+        match tiling {
+            Tiling::Square => (width, height),
+            Tiling::Hex => (width + 0.5, height + 1.0 / 3.0),
+            Tiling::Triangle => (width + 1.0, height),
+        }
+    }
+
+    pub fn cell_size(&self) -> Dimensions {
+        self.cell
+    }
+
+    /// The centre of a cell's box.
+    pub fn centre(&self, column: usize, row: usize) -> Point {
+        [self.origin[0] + (column as Scalar + 0.5) * self.cell[0],
+         self.origin[1] - (row as Scalar + 0.5) * self.cell[1]]
+    }
+
+    /// How a cell is drawn.
+    pub fn shape(&self, column: usize, row: usize) -> Shape {
+        match self.tiling {
+            Tiling::Square => Shape::Square,
+            Tiling::Hex => Shape::Hex { odd_row: row % 2 == 1 },
+            Tiling::Triangle => Shape::Triangle { up: (column + row) % 2 == 0 },
+        }
+    }
+
+    /// The cell whose shape holds `point`. Hexagons and triangles reach into the boxes of the
+    /// cells around them, so those are tried after the cell whose box holds `point`.
+    pub fn cell_at(&self, point: Point) -> Option<(usize, usize)> {
+        let row = ((self.origin[1] - point[1]) / self.cell[1]).floor();
+        let column = ((point[0] - self.origin[0]) / self.cell[0]).floor();
+        for &row_offset in [0.0, -1.0, 1.0].iter() {
+            for &column_offset in [0.0, -1.0, 1.0].iter() {
+                let (column, row) = (column + column_offset, row + row_offset);
+                if column < 0.0 || column >= self.width as Scalar ||
+                   row < 0.0 || row >= self.height as Scalar {
+                    continue;
+                }
+                let (column, row) = (column as usize, row as usize);
+                if self.shape(column, row).contains(self.centre(column, row), self.cell, point) {
+                    return Some((column, row));
+                }
+            }
+        }
+        None
+    }
+
+    /// The columns and rows with any part inside `rect`, with a cell to spare for shapes that
+    /// reach outside their boxes.
+    pub fn visible(&self, rect: Rect) -> (Range<usize>, Range<usize>) {
+        let clamp = |value: Scalar, limit: usize| {
+            cmp::min(value.max(0.0) as usize, limit)
+        };
+        let columns = clamp((rect.left() - self.origin[0]) / self.cell[0] - 1.0, self.width)..
+                      clamp((rect.right() - self.origin[0]) / self.cell[0] + 2.0, self.width);
+        let rows = clamp((self.origin[1] - rect.top()) / self.cell[1] - 1.0, self.height)..
+                   clamp((self.origin[1] - rect.bottom()) / self.cell[1] + 2.0, self.height);
+        (columns, rows)
+    }
+}
+
+/// The unopened, unflagged cells that look pressed while buttons are held on `held`: the cell
+/// itself for opening it, and its neighbours too for a chord.
+pub fn pressed_cells(board: &Board, held: Option<(Coord, Action)>) -> Vec<Coord> {
+    let mut cells = match held {
+        Some((coord, Action::Open)) => vec![coord],
+        Some((coord, Action::Chord)) => {
+            let mut cells = vec![coord];
+            cells.extend(board.neighbours(coord));
+            cells
+        },
+        _ => vec![],
+    };
+    cells.retain(|&coord| board.tile(coord) == Tile::Unknown ||
+                          board.tile(coord) == Tile::Questioned);
+    cells
+}
+
+/// The largest size a board with square cells can take inside `area`. A board that doesn't fit
+/// with cells of `MIN_CELL_SIZE` fills as much of `area` as it can at that size.
+pub fn fitted(board: &Board, area: Dimensions) -> Dimensions {
+    let (units_w, units_h) = Layout::units(board.topology.tiling, board.width, board.height);
+    let size = (area[0] / units_w).min(area[1] / units_h).max(MIN_CELL_SIZE).min(MAX_CELL_SIZE);
+    [(units_w * size).min(area[0]), (units_h * size).min(area[1])]
+}
+
+impl<'a, F> BoardView<'a, F> {
+    /// Draw one layer of `board`.
+    pub fn new(board: &'a Board, layer: usize) -> BoardView<'a, F> {
+        BoardView {
+            common: CommonBuilder::new(),
+            board: board,
+            layer: layer,
+            viewport: Viewport::fit(),
+            drag_pans: false,
+            maybe_bindings: None,
+            modifiers: Modifiers::none(),
+            skinned: false,
+            maybe_react: None,
+            style: Style::new(),
+            enabled: true,
+        }
+    }
+
+    /// Zoom and pan the board. Cells falling outside the widget aren't drawn.
+    pub fn viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    /// Pan by dragging with the left button instead of clicking.
+    pub fn drag_pans(mut self, drag_pans: bool) -> Self {
+        self.drag_pans = drag_pans;
+        self
+    }
+
+    /// Read clicks with `bindings`, while `modifiers` are held.
+    pub fn bindings(mut self, bindings: &'a Bindings, modifiers: Modifiers) -> Self {
+        self.maybe_bindings = Some(bindings);
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Leave the cells that a skin has sprites for undrawn, as they are drawn over the `Ui`.
+    pub fn skinned(mut self, skinned: bool) -> Self {
+        self.skinned = skinned;
+        self
+    }
+
+    /// Set the reaction to clicks on cells, triggered when the buttons are released, and to
+    /// zooming and panning.
+    pub fn react(mut self, reaction: F) -> Self {
+        self.maybe_react = Some(reaction);
+        self
+    }
+
+    /// Colour the cells and their numbers after `palette`.
+    pub fn palette(mut self, palette: &Palette) -> Self {
+        self.style.maybe_color = Some(palette.unopened);
+        self.style.maybe_opened_color = Some(palette.opened);
+        self.style.maybe_mine_color = Some(palette.mine);
+        self.style.maybe_label_color = Some(palette.text);
+        self.style.maybe_number_colors = Some(palette.numbers);
+        self
+    }
+
+    /// If true, will allow user inputs.  If false, will disallow user inputs.
+    #[allow(dead_code)]
+    pub fn enabled(mut self, flag: bool) -> Self {
+        self.enabled = flag;
+        self
+    }
+}
+
+impl<'a, F> Widget for BoardView<'a, F>
+    where F: FnMut(Event)
+{
+    type State = State;
+    type Style = Style;
+
+    fn common(&self) -> &CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut CommonBuilder {
+        &mut self.common
+    }
+
+    fn unique_kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn init_state(&self) -> State {
+        State {
+            interaction: Interaction::Normal,
+            hovered: None,
+            pressed: None,
+            maybe_drag_from: None,
+            slots: Vec::new(),
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.style.clone()
+    }
+
+    fn update<B: Backend>(mut self, args: UpdateArgs<Self, B>) {
+        let UpdateArgs { idx, state, rect, mut ui, style, .. } = args;
+        use conrod::MouseButtonPosition::Down;
+
+        let layout = Layout::new(self.board, rect, self.viewport);
+        let layer = self.layer;
+        let maybe_mouse = ui.input(idx).maybe_mouse;
+
+        let classic;
+        let bindings = match self.maybe_bindings {
+            Some(bindings) => bindings,
+            None => {
+                classic = Bindings::default();
+                &classic
+            },
+        };
+        let modifiers = self.modifiers;
+        let action = |interaction: Interaction| {
+            interaction.button().and_then(|button| bindings.mouse(button, modifiers))
+        };
+        let middle_clicks = bindings.mouse(Button::Middle, modifiers).is_some();
+
+        // Wheel zooms around the pointer, a tenth at a notch.
+        if let Some(mouse) = maybe_mouse {
+            if mouse.scroll.y != 0.0 {
+                let around = [mouse.xy[0] - rect.x(), mouse.xy[1] - rect.y()];
+                let viewport = self.viewport.zoom_by(1.1f64.powf(mouse.scroll.y), around);
+                if let Some(ref mut react) = self.maybe_react {
+                    react(Event::View(viewport));
+                }
+            }
+        }
+
+        // Dragging moves the board along with the mouse, and doesn't click.
+        let dragging = maybe_mouse.map_or(false, |mouse| {
+            !middle_clicks && mouse.middle.position == Down ||
+            self.drag_pans && mouse.left.position == Down
+        });
+        let maybe_drag_from = if dragging { maybe_mouse.map(|mouse| mouse.xy) } else { None };
+        if let (Some(from), Some(to)) = (state.view().maybe_drag_from, maybe_drag_from) {
+            if from != to {
+                let viewport = self.viewport.pan_by([to[0] - from[0], to[1] - from[1]]);
+                if let Some(ref mut react) = self.maybe_react {
+                    react(Event::View(viewport));
+                }
+            }
+        }
+        match (state.view().maybe_drag_from.is_some(), dragging) {
+            (false, true) => ui.capture_mouse(idx),
+            (true, false) => ui.uncapture_mouse(idx),
+            _ => (),
+        }
+        if state.view().maybe_drag_from != maybe_drag_from {
+            state.update(|state| state.maybe_drag_from = maybe_drag_from);
+        }
+
+        // One cell under the mouse at a time, worked out from the layout rather than by asking
+        // every cell.
+        let hovered = maybe_mouse.and_then(|mouse| layout.cell_at(mouse.xy))
+            .map(|(column, row)| Coord::new(column, row, layer));
+        let new_interaction = match (self.enabled && !dragging, maybe_mouse) {
+            (false, _) | (true, None) => Interaction::Normal,
+            (true, Some(mouse)) => {
+                cell::get_new_interaction(hovered.is_some(), state.view().interaction, mouse,
+                                          middle_clicks)
+            },
+        };
+
+        // The buttons take the cell they go down on, and a click happens when they are released
+        // on that same cell: dragging off it before letting go cancels the click.
+        let prev_interaction = state.view().interaction;
+        let pressed = match (prev_interaction.is_pressed(), new_interaction.is_pressed()) {
+            (false, true) => hovered,
+            (true, true) => state.view().pressed,
+            _ => None,
+        };
+        if let (true, false, Some(coord)) = (prev_interaction.is_pressed(),
+                                             new_interaction.is_pressed(),
+                                             state.view().pressed) {
+            if let (true, Some(action)) = (hovered == Some(coord), action(prev_interaction)) {
+                if let Some(ref mut react) = self.maybe_react {
+                    react(Event::Click(coord, action));
+                }
+            }
+        }
+
+        // The cell held on with a bound button, while the mouse is still over it.
+        let held_on = |pressed: Option<Coord>, hovered: Option<Coord>, interaction| {
+            match (pressed, action(interaction)) {
+                (Some(coord), Some(action)) if hovered == Some(coord) => Some((coord, action)),
+                _ => None,
+            }
+        };
+        let held = held_on(pressed, hovered, new_interaction);
+        if let Some(ref mut react) = self.maybe_react {
+            if held != held_on(state.view().pressed, state.view().hovered, prev_interaction) {
+                react(Event::Held(held));
+            }
+            if hovered != state.view().hovered {
+                react(Event::Hover(hovered));
+            }
+        }
+
+        // Capture the mouse while a button is held, so the board sees it being released.
+        match (state.view().pressed.is_some(), pressed.is_some()) {
+            (false, true) => ui.capture_mouse(idx),
+            (true, false) => ui.uncapture_mouse(idx),
+            _ => (),
+        }
+
+        if prev_interaction != new_interaction || state.view().hovered != hovered ||
+           state.view().pressed != pressed {
+            state.update(|state| {
+                state.interaction = new_interaction;
+                state.hovered = hovered;
+                state.pressed = pressed;
+            });
+        }
+
+        let (columns, rows) = layout.visible(rect);
+        let shown = (columns.end - columns.start) * (rows.end - rows.start);
+        if state.view().slots.len() < shown {
+            state.update(|state| {
+                let missing = shown - state.slots.len();
+                state.slots.extend((0..missing).map(|_| Slots::new()));
+            });
+        }
+
+        let font_size = cmp::min(style.label_font_size(ui.theme()),
+                                 (layout.cell_size()[1] * 0.6) as FontSize);
+        let show_labels = font_size >= MIN_FONT_SIZE;
+        let skinned = self.skinned && Sprites::covers(self.board);
+        let pressed_cells = pressed_cells(self.board, held);
+        let mut slots = state.view().slots.iter();
+        for row in rows {
+            for column in columns.clone() {
+                let coord = Coord::new(column, row, layer);
+                let tile = self.board.tile(coord);
+                let slots = slots.next().unwrap();
+                if skinned && Sprite::for_tile(tile).is_some() {
+                    continue;
+                }
+                // Pressed cells sink to look opened. The cell under the mouse lights up, or
+                // darkens while the buttons held on it would click it.
+                let mut color = cell::tile_color(tile, &style, ui.theme());
+                if pressed_cells.contains(&coord) {
+                    color = style.opened_color();
+                } else if held.map(|(coord, _)| coord) == Some(coord) ||
+                          hovered == Some(coord) && !new_interaction.is_pressed() {
+                    color = new_interaction.color(color);
+                }
+                let label_color = cell::label_color(tile, &style, ui.theme());
+                cell::draw(&mut ui, idx, slots, layout.shape(column, row),
+                           layout.centre(column, row), layout.cell_size(), color,
+                           if show_labels { cell::label(tile) } else { "" }, label_color,
+                           font_size);
+            }
+        }
+    }
+}
+
+/// Provide the chainable color() configuration method, for the unopened cells.
+impl<'a, F> Colorable for BoardView<'a, F> {
+    fn color(mut self, color: Color) -> Self {
+        self.style.maybe_color = Some(color);
+        self
+    }
+}
+
+/// Provide the chainable label_color() and label_font_size() configuration methods. The labels
+/// themselves come from the board.
+impl<'a, F> Labelable<'a> for BoardView<'a, F> {
+    fn label(self, _text: &'a str) -> Self {
+        self
+    }
+    fn label_color(mut self, color: Color) -> Self {
+        self.style.maybe_label_color = Some(color);
+        self
+    }
+    fn label_font_size(mut self, size: FontSize) -> Self {
+        self.style.maybe_label_font_size = Some(size);
+        self
+    }
+}
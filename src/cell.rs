@@ -1,418 +1,331 @@
-//! A demonstration of designing a custom, third-party widget.
+//! One cell of the board as the GUI draws it: its outline, colour, label and how the mouse
+//! buttons held over it are read.
 //!
-//! In this case, we'll design a simple circular button.
-//!
-//! All of the custom widget design will occur within the `circular_button` module.
-//!
-//! We'll *use* our fancy circular button in the `main` function (below the circular_button module).
-//!
-//! Note that in this case, we use `piston_window` to draw our widget, however in practise you may
-//! use any backend you wish.
-//!
-//! For more information, please see the `Widget` trait documentation.
-
-/// The module in which we'll implement our own custom circular button.
-// mod cell {
-    use conrod::{
-        Backend,
-        Color,
-        Colorable,
-        CommonBuilder,
-        Dimensions,
-        FontSize,
-        IndexSlot,
-        Labelable,
-        Mouse,
-        Point,
-        Positionable,
-        Rectangle,
-        Scalar,
-        Text,
-        Theme,
-        UpdateArgs,
-        Widget,
-        WidgetKind,
-    };
-
-
-    /// The type upon which we'll implement the `Widget` trait.
-    pub struct Cell<'a, F> {
-        /// An object that handles some of the dirty work of rendering a GUI. We don't
-        /// really have to worry about it.
-        common: CommonBuilder,
-        /// Optional label string for the button.
-        maybe_label: Option<&'a str>,
-        /// Optional callback for when the button is pressed. If you want the button to
-        /// do anything, this callback must exist.
-        maybe_react: Option<F>,
-        /// See the Style struct below.
-        style: Style,
-        /// Whether the button is currently enabled, i.e. whether it responds to
-        /// user input.
-        enabled: bool
-    }
-
-    /// Represents the unique styling for our Cell widget.
-    #[derive(Clone, Debug, PartialEq)]
-    pub struct Style {
-        /// Color of the button.
-        pub maybe_color: Option<Color>,
-        /// Radius of the button.
-        pub maybe_radius: Option<Scalar>,
-        /// Color of the button's label.
-        pub maybe_label_color: Option<Color>,
-        /// Font size of the button's label.
-        pub maybe_label_font_size: Option<u32>,
+//! Cells used to be widgets of their own, one per cell with a rectangle and a text each, which
+//! doesn't scale past small boards. They are now drawn by the `BoardView` widget (see
+//! `board_view.rs`), which only sets the primitives of the cells on screen.
+
+use conrod::{
+    Backend,
+    Color,
+    Colorable,
+    Dimensions,
+    FontSize,
+    IndexSlot,
+    Mouse,
+    NodeIndex,
+    Point,
+    Polygon,
+    Positionable,
+    Rectangle,
+    Text,
+    Theme,
+    UiCell,
+    Widget,
+};
+use conrod::color;
+
+use minesweeper::bindings::Button;
+use minesweeper::board::Tile;
+
+use board_view::KIND;
+
+/// The outline of a cell, following the board's topology.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Square,
+    /// A pointy-topped hexagon. Hexagons on odd rows are drawn half a cell to the right, and
+    /// reach a third of a row into the rows above and below so that the rows interlock.
+    Hex { odd_row: bool },
+    /// A triangle twice as wide as the cell, pointing up or down, so that it interlocks with
+    /// the triangles on either side.
+    Triangle { up: bool },
+}
+
+impl Shape {
+    /// Whether `point` is inside the shape drawn for the cell whose box is centred on `xy`.
+    pub fn contains(&self, xy: Point, dim: Dimensions, point: Point) -> bool {
+        let (dx, dy) = (point[0] - xy[0], point[1] - xy[1]);
+        match *self {
+            Shape::Square => dx.abs() <= dim[0] / 2.0 && dy.abs() <= dim[1] / 2.0,
+            // Half as tall at the sides as in the middle.
+            Shape::Hex { odd_row } => {
+                let (w, h) = (dim[0] / 2.0, dim[1] * 2.0 / 3.0);
+                let dx = (dx - if odd_row { w } else { 0.0 }).abs();
+                dx <= w && dy.abs() <= h * (1.0 - dx / (2.0 * w))
+            },
+            // Narrowing from the base to the tip.
+            Shape::Triangle { up } => {
+                let h = dim[1] / 2.0;
+                let from_base = (if up { dy + h } else { h - dy }) / (2.0 * h);
+                from_base >= 0.0 && from_base <= 1.0 && dx.abs() <= dim[0] * (1.0 - from_base)
+            },
+        }
     }
-
-    /// Represents the unique, cached state for our Cell widget.
-    #[derive(Clone, Debug, PartialEq)]
-    pub struct State {
-        /// The current interaction state. See the Interaction enum below. See also
-        /// get_new_interaction below, where we define all the logic for transitioning between
-        /// interaction states.
-        interaction: Interaction,
-        /// An index to use for our **Circle** primitive graphics widget.
-        circle_idx: IndexSlot,
-        /// An index to use for our **Text** primitive graphics widget (for the label).
-        text_idx: IndexSlot,
+}
+
+/// Represents the unique styling for the cells of a board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+    /// Color of the unopened cells.
+    pub maybe_color: Option<Color>,
+    /// Color of the opened cells.
+    pub maybe_opened_color: Option<Color>,
+    /// Color of the mines.
+    pub maybe_mine_color: Option<Color>,
+    /// Color of the cells' labels.
+    pub maybe_label_color: Option<Color>,
+    /// Colors of the numbers 1 to 8, instead of the label color.
+    pub maybe_number_colors: Option<[Color; 8]>,
+    /// Font size of the cells' labels.
+    pub maybe_label_font_size: Option<u32>,
+}
+
+/// The primitives drawing one cell on screen, kept between updates so that conrod can reuse
+/// their state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slots {
+    rectangle: IndexSlot,
+    polygon: IndexSlot,
+    text: IndexSlot,
+}
+
+impl Slots {
+    pub fn new() -> Slots {
+        Slots {
+            rectangle: IndexSlot::new(),
+            polygon: IndexSlot::new(),
+            text: IndexSlot::new(),
+        }
     }
-
-    /// A `&'static str` that can be used to uniquely identify our widget type.
-    pub const KIND: WidgetKind = "Cell";
-
-    /// A type to keep track of interaction between updates.
-    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
-    pub enum Interaction {
-        Normal,
-        Highlighted,
-        LeftClicked,
-        RightClicked,
-        BothClicked,
+}
+
+/// A type to keep track of interaction between updates.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Interaction {
+    Normal,
+    Highlighted,
+    LeftClicked,
+    RightClicked,
+    BothClicked,
+    MiddleClicked,
+}
+
+impl Interaction {
+    /// Whether buttons are held down on the board.
+    pub fn is_pressed(&self) -> bool {
+        self.button().is_some()
     }
 
-    impl Interaction {
-        /// Alter the widget color depending on the current interaction.
-        fn color(&self, color: Color) -> Color {
-            match *self {
-                /// The base color as defined in the Style struct, or a default provided
-                /// by the current Theme if the Style has no color.
-                Interaction::Normal => color,
-                /// The Color object (from Elmesque) can calculate a highlighted version
-                /// of itself. We don't have to use it, though. We could specify any color
-                /// we want.
-                Interaction::Highlighted => color.highlighted(),
-                /// Ditto for clicked.
-                Interaction::LeftClicked => color.clicked(),
-                Interaction::RightClicked => color.clicked(),
-                Interaction::BothClicked => color.clicked(),
-            }
+    /// The button held, as the bindings name it.
+    pub fn button(&self) -> Option<Button> {
+        match *self {
+            Interaction::Normal | Interaction::Highlighted => None,
+            Interaction::LeftClicked => Some(Button::Left),
+            Interaction::RightClicked => Some(Button::Right),
+            Interaction::BothClicked => Some(Button::Both),
+            Interaction::MiddleClicked => Some(Button::Middle),
         }
     }
 
-    /// Check the current interaction with the button. Takes into account whether the mouse is
-    /// over the button and the previous interaction state.
-    fn get_new_interaction(is_over: bool, prev: Interaction, mouse: Mouse) -> Interaction {
-        use conrod::MouseButtonPosition::{Down, Up};
-        use self::Interaction::{Normal, Highlighted, LeftClicked, RightClicked, BothClicked};
-        // println!("{:?}, {:?}", mouse.left.position, mouse.right.position);
-        match (is_over, prev, mouse.left.position, mouse.right.position) {
-            // LMB is down over the button. But the button wasn't Highlighted last
-            // update. This means the user clicked somewhere outside the button and
-            // moved over the button holding LMB down. We do nothing in this case.
-            (true,  Normal,  Down, _) => Normal,
-            (true,  Normal,  _, Down) => Normal,
-
-            // LMB is down over the button. The button was either Highlighted or Clicked
-            // last update. If it was highlighted before, that means the user clicked
-            // just now, and we transition to the Clicked state. If it was clicked
-            // before, that means the user is still holding LMB down from a previous
-            // click, in which case the state remains Clicked.
-            (true, _, Down, Down) => BothClicked,
-
-            (true,  _,       Down, _   ) => LeftClicked,
-            (true,  _,       _   , Down) => RightClicked,
-
-            // LMB is up. The mouse is hovering over the button. Regardless of what the
-            // state was last update, the state should definitely be Highlighted now.
-            (true,  _,       Up, Up)   => Highlighted,
-
-            // LMB is down, the mouse is not over the button, but the previous state was
-            // Clicked. That means the user clicked the button and then moved the mouse
-            // outside the button while holding LMB down. The button stays Clicked.
-            (false, LeftClicked, Down, _) => LeftClicked,
-
-            (false, RightClicked, _, Down) => RightClicked,
-
-
-            // If none of the above applies, then nothing interesting is happening with
-            // this button.
-            _                      => Normal,
+    /// Alter the cell color depending on the current interaction.
+    pub fn color(&self, color: Color) -> Color {
+        match *self {
+            Interaction::Normal => color,
+            Interaction::Highlighted => color.highlighted(),
+            Interaction::LeftClicked | Interaction::RightClicked | Interaction::BothClicked |
+            Interaction::MiddleClicked => color.clicked(),
         }
     }
-
-    /// Return whether or not a given point is over a circle at a given point on a
-    /// Cartesian plane. We use this to determine whether the mouse is over the button.
-    pub fn is_over_circ(circ_center: Point, mouse_point: Point, dim: Dimensions) -> bool {
-        true
+}
+
+/// Check the current interaction with the board. Takes into account whether the mouse is over a
+/// cell and the previous interaction state. The middle button is only read when `middle_clicks`
+/// is set, as otherwise it pans the board.
+pub fn get_new_interaction(is_over: bool, prev: Interaction, mouse: Mouse, middle_clicks: bool)
+                           -> Interaction {
+    use conrod::MouseButtonPosition::{Down, Up};
+    use self::Interaction::{Normal, Highlighted, LeftClicked, RightClicked, BothClicked,
+                            MiddleClicked};
+    let middle = if middle_clicks { mouse.middle.position } else { Up };
+    match (is_over, prev, mouse.left.position, mouse.right.position, middle) {
+        // A button is down over a cell, but the board wasn't Highlighted last update. This means
+        // the user clicked somewhere outside the board and moved over it holding the button down.
+        // We do nothing in this case.
+        (true,  Normal,  Down, _, _) => Normal,
+        (true,  Normal,  _, Down, _) => Normal,
+        (true,  Normal,  _, _, Down) => Normal,
+
+        // A button is down over a cell and the board was either Highlighted or Clicked last
+        // update: the user clicked just now, or is still holding the button down.
+        (true, _, Down, Down, _) => BothClicked,
+
+        // Once both buttons are down, the chord waits for both to be released, rather than
+        // turning into a click of the button still held.
+        (_, BothClicked, Down, _, _) | (_, BothClicked, _, Down, _) => BothClicked,
+
+        (true,  _,       Down, _   , _   ) => LeftClicked,
+        (true,  _,       _   , Down, _   ) => RightClicked,
+        (true,  _,       _   , _   , Down) => MiddleClicked,
+
+        // The buttons are up and the mouse is hovering over a cell.
+        (true,  _,       Up, Up, Up) => Highlighted,
+
+        // A button is down, the mouse is not over a cell, but the board was Clicked last update.
+        // That means the user clicked a cell and moved off it holding the button down.
+        (false, LeftClicked, Down, _, _) => LeftClicked,
+
+        (false, RightClicked, _, Down, _) => RightClicked,
+
+        (false, MiddleClicked, _, _, Down) => MiddleClicked,
+
+        // If none of the above applies, then nothing interesting is happening with the board.
+        _                      => Normal,
     }
-
-    impl<'a, F> Cell<'a, F> {
-        /// Create a button context to be built upon.
-        pub fn new() -> Cell<'a, F> {
-            Cell {
-                common: CommonBuilder::new(),
-                maybe_react: None,
-                maybe_label: None,
-                style: Style::new(),
-                enabled: true,
-            }
-        }
-
-        /// Set the reaction for the Button. The reaction will be triggered upon release
-        /// of the button. Like other Conrod configs, this returns self for chainability.
-        pub fn react(mut self, reaction: F) -> Self {
-            self.maybe_react = Some(reaction);
-            self
-        }
-
-        /// If true, will allow user inputs.  If false, will disallow user inputs.  Like
-        /// other Conrod configs, this returns self for chainability. Allow dead code
-        /// because we never call this in the example.
-        #[allow(dead_code)]
-        pub fn enabled(mut self, flag: bool) -> Self {
-            self.enabled = flag;
-            self
-        }
+}
+
+/// The label shown on a tile. A cube's cells have up to 26 neighbours.
+pub fn label(tile: Tile) -> &'static str {
+    const NUMBER_LABELS: [&'static str; 27] = [
+        " ", "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16",
+        "17", "18", "19", "20", "21", "22", "23", "24", "25", "26",
+    ];
+    match tile {
+        Tile::Number(n) if n < NUMBER_LABELS.len() => NUMBER_LABELS[n],
+        Tile::Number(_) => "#",
+        Tile::Mine => "*",
+        Tile::Unknown => " ",
+        Tile::Flagged => "!",
+        Tile::Questioned => "?",
     }
+}
+
+/// The colour a tile is filled with.
+pub fn tile_color(tile: Tile, style: &Style, theme: &Theme) -> Color {
+    match tile {
+        Tile::Mine => style.mine_color(),
+        Tile::Number(_) => style.opened_color(),
+        Tile::Unknown | Tile::Flagged | Tile::Questioned => style.color(theme),
+    }
+}
 
-    /// A custom Conrod widget must implement the Widget trait. See the **Widget** trait
-    /// documentation for more details.
-    impl<'a, F> Widget for Cell<'a, F>
-        where F: FnMut(Interaction)
-    {
-        /// The State struct that we defined above.
-        type State = State;
-
-        /// The Style struct that we defined above.
-        type Style = Style;
-
-        fn common(&self) -> &CommonBuilder {
-            &self.common
-        }
-
-        fn common_mut(&mut self) -> &mut CommonBuilder {
-            &mut self.common
-        }
-
-        fn unique_kind(&self) -> &'static str {
-            KIND
-        }
-
-        fn init_state(&self) -> State {
-            State {
-                interaction: Interaction::Normal,
-                circle_idx: IndexSlot::new(),
-                text_idx: IndexSlot::new(),
-            }
-        }
-
-        fn style(&self) -> Style {
-            self.style.clone()
-        }
-
-        /// Update the state of the button. The state may or may not have changed since
-        /// the last update. (E.g. it may have changed because the user moused over the
-        /// button.) If the state has changed, return the new state. Else, return None.
-        fn update<B: Backend>(mut self, args: UpdateArgs<Self, B>) {
-            let UpdateArgs { idx, state, rect, mut ui, style, .. } = args;
-            let (xy, dim) = rect.xy_dim();
-            let maybe_mouse = ui.input(idx).maybe_mouse.map(|mouse| mouse.relative_to(xy));
-
-            // Check whether or not a new interaction has occurred.
-            let new_interaction = match (self.enabled, maybe_mouse) {
-                (false, _) | (true, None) => Interaction::Normal,
-                (true, Some(mouse)) => {
-                    // Conrod does us a favor by transforming mouse.xy into this widget's
-                    // local coordinate system. Because mouse.xy is in local coords,
-                    // we must also pass the circle center in local coords. Thus we pass
-                    // [0.0, 0.0] as the center.
-                    //
-                    // See above where we define is_over_circ.
-                    let is_over = is_over_circ([0.0, 0.0], mouse.xy, dim);
-
-                    // See above where we define get_new_interaction.
-                    get_new_interaction(is_over, state.view().interaction, mouse)
-                },
-            };
-
-            if let (Interaction::BothClicked, Interaction::Highlighted) =
-                (state.view().interaction, new_interaction)
-            {
-                // Recall that our Cell struct includes maybe_react, which
-                // stores either a reaction function or None. If maybe_react is Some, call
-                // the function.
-                if let Some(ref mut react) = self.maybe_react {
-                    react(Interaction::BothClicked);
-                }
-            }
-
-
-            // If the mouse was released over the button, react. state.interaction is the
-            // button's state as of a moment ago. new_interaction is the updated state as
-            // of right now. So this if statement is saying: If the button was clicked a
-            // moment ago, and it's now highlighted, then the button has been activated.
-            if let (Interaction::LeftClicked, Interaction::Highlighted) =
-                (state.view().interaction, new_interaction)
-            {
-                // Recall that our Cell struct includes maybe_react, which
-                // stores either a reaction function or None. If maybe_react is Some, call
-                // the function.
-                if let Some(ref mut react) = self.maybe_react {
-                    react(Interaction::LeftClicked);
-                }
-            }
-
-            if let (Interaction::RightClicked, Interaction::Highlighted) =
-                (state.view().interaction, new_interaction)
-            {
-                // Recall that our Cell struct includes maybe_react, which
-                // stores either a reaction function or None. If maybe_react is Some, call
-                // the function.
-                if let Some(ref mut react) = self.maybe_react {
-                    react(Interaction::RightClicked);
-                }
-            }
-
-            // Here we check to see whether or not our button should capture the mouse.
-            //
-            // Widgets can "capture" user input. If the button captures the mouse, then mouse
-            // events will only be seen by the button. Other widgets will not see mouse events
-            // until the button uncaptures the mouse.
-            match (state.view().interaction, new_interaction) {
-                // If the user has pressed the button we capture the mouse.
-                (Interaction::Highlighted, Interaction::LeftClicked) |
-                (Interaction::Highlighted, Interaction::RightClicked) => {
-                    ui.capture_mouse(idx);
-                },
-                // If the user releases the button, we uncapture the mouse.
-                (Interaction::LeftClicked, Interaction::Highlighted) |
-                (Interaction::LeftClicked, Interaction::Normal) |
-                (Interaction::RightClicked, Interaction::Highlighted) |
-                (Interaction::RightClicked, Interaction::Normal) => {
-                    ui.uncapture_mouse(idx);
-                },
-                _ => (),
-            }
-
-            // Whenever we call `state.update` (as below), a flag is set within our `State`
-            // indicating that there has been some mutation and that our widget requires a
-            // re-draw. Thus, we only want to call `state.update` if there has been some change in
-            // order to only re-draw when absolutely required.
-            //
-            // You can see how we do this below - we check if the state has changed before calling
-            // `state.update`.
-
-            // If the interaction has changed, set the new interaction.
-            if state.view().interaction != new_interaction {
-                state.update(|state| state.interaction = new_interaction);
-            }
-
-            // Finally, we'll describe how we want our widget drawn by simply instantiating the
-            // necessary primitive graphics widgets.
-            //
-            // Conrod will automatically determine whether or not any changes have occurred and
-            // whether or not any widgets need to be re-drawn.
-            //
-            // The primitive graphics widgets are special in that their unique state is used within
-            // conrod's backend to do the actual drawing. This allows us to build up more complex
-            // widgets by using these simple primitives with our familiar layout, coloring, etc
-            // methods.
-            //
-            // If you notice that conrod is missing some sort of primitive graphics that you
-            // require, please file an issue or open a PR so we can add it! :)
-
-            // First, we'll draw the **Circle** with a radius that is half our given width.
-            let radius = rect.w() / 2.0;
-            let color = new_interaction.color(style.color(ui.theme()));
-            let circle_idx = state.view().circle_idx.get(&mut ui);
-            Rectangle::fill([radius, radius])
-                .middle_of(idx)
-                .graphics_for(idx)
+/// The colour of a tile's label.
+pub fn label_color(tile: Tile, style: &Style, theme: &Theme) -> Color {
+    match (tile, style.maybe_number_colors) {
+        (Tile::Number(n), Some(colors)) if n >= 1 && n <= colors.len() => colors[n - 1],
+        _ => style.label_color(theme),
+    }
+}
+
+/// Set the primitives drawing one cell whose box is centred on `xy`, on behalf of the board
+/// widget `parent`, which crops them to its rectangle.
+pub fn draw<B: Backend>(ui: &mut UiCell<B>, parent: NodeIndex, slots: &Slots, shape: Shape,
+                        xy: Point, dim: Dimensions, color: Color, label: &str, label_color: Color,
+                        font_size: FontSize) {
+    let (x, y) = (xy[0], xy[1]);
+    let (label_x, label_y) = match shape {
+        // A square a little smaller than the box, leaving a gap between cells.
+        Shape::Square => {
+            let idx = slots.rectangle.get(ui);
+            Rectangle::fill([dim[0] * 0.9, dim[1] * 0.9])
+                .x_y(x, y)
+                .parent(parent)
+                .graphics_for(parent)
                 .color(color)
-                .set(circle_idx, &mut ui);
-
-            // Now we'll instantiate our label using the **Text** widget.
-            let label_color = style.label_color(ui.theme());
-            let font_size = style.label_font_size(ui.theme());
-            let text_idx = state.view().text_idx.get(&mut ui);
-            if let Some(ref label) = self.maybe_label {
-                Text::new(label)
-                    .middle_of(idx)
-                    .font_size(font_size)
-                    .graphics_for(idx)
-                    .color(label_color)
-                    .set(text_idx, &mut ui);
-            }
-        }
+                .set(idx, ui);
+            (x, y)
+        },
+        // A hexagon as wide as the cell, whose top and bottom points overlap the rows above and
+        // below by a third of a row.
+        Shape::Hex { odd_row } => {
+            let (w, h) = (dim[0] / 2.0, dim[1] * 2.0 / 3.0);
+            let x = x + if odd_row { w } else { 0.0 };
+            let points = [
+                [x, y + h],
+                [x + w, y + h / 2.0],
+                [x + w, y - h / 2.0],
+                [x, y - h],
+                [x - w, y - h / 2.0],
+                [x - w, y + h / 2.0],
+            ];
+            let idx = slots.polygon.get(ui);
+            Polygon::fill(points.iter().cloned())
+                .parent(parent)
+                .graphics_for(parent)
+                .color(color)
+                .set(idx, ui);
+            (x, y)
+        },
+        // The label sits at the centroid, a third of the way from the base.
+        Shape::Triangle { up } => {
+            let (w, h) = (dim[0], dim[1] / 2.0);
+            let (base, tip) = if up { (y - h, y + h) } else { (y + h, y - h) };
+            let points = [[x - w, base], [x + w, base], [x, tip]];
+            let idx = slots.polygon.get(ui);
+            Polygon::fill(points.iter().cloned())
+                .parent(parent)
+                .graphics_for(parent)
+                .color(color)
+                .set(idx, ui);
+            (x, base + (tip - base) / 3.0)
+        },
+    };
 
+    // Blank labels are left out, so only opened numbers, flags and mines cost a text.
+    if label.trim().is_empty() {
+        return;
     }
-
-    impl Style {
-        /// Construct the default Style.
-        pub fn new() -> Style {
-            Style {
-                maybe_color: None,
-                maybe_radius: None,
-                maybe_label_color: None,
-                maybe_label_font_size: None,
-            }
+    let idx = slots.text.get(ui);
+    Text::new(label)
+        .x_y(label_x, label_y)
+        .font_size(font_size)
+        .parent(parent)
+        .graphics_for(parent)
+        .color(label_color)
+        .set(idx, ui);
+}
+
+impl Style {
+    /// Construct the default Style.
+    pub fn new() -> Style {
+        Style {
+            maybe_color: None,
+            maybe_opened_color: None,
+            maybe_mine_color: None,
+            maybe_label_color: None,
+            maybe_number_colors: None,
+            maybe_label_font_size: None,
         }
+    }
 
-        /// Get the Color for an Element.
-        pub fn color(&self, theme: &Theme) -> Color {
-            self.maybe_color.or(theme.widget_style::<Self>(KIND).map(|default| {
-                default.style.maybe_color.unwrap_or(theme.shape_color)
-            })).unwrap_or(theme.shape_color)
-        }
+    /// Get the Color of unopened cells.
+    pub fn color(&self, theme: &Theme) -> Color {
+        self.maybe_color.or(theme.widget_style::<Self>(KIND).map(|default| {
+            default.style.maybe_color.unwrap_or(theme.shape_color)
+        })).unwrap_or(theme.shape_color)
+    }
 
-        /// Get the label Color for an Element.
-        pub fn label_color(&self, theme: &Theme) -> Color {
-            self.maybe_label_color.or(theme.widget_style::<Self>(KIND).map(|default| {
-                default.style.maybe_label_color.unwrap_or(theme.label_color)
-            })).unwrap_or(theme.label_color)
-        }
+    /// Get the Color of opened cells.
+    pub fn opened_color(&self) -> Color {
+        self.maybe_opened_color.unwrap_or(color::rgb(0.8, 0.8, 0.8))
+    }
 
-        /// Get the label font size for an Element.
-        pub fn label_font_size(&self, theme: &Theme) -> FontSize {
-            self.maybe_label_font_size.or(theme.widget_style::<Self>(KIND).map(|default| {
-                default.style.maybe_label_font_size.unwrap_or(theme.font_size_medium)
-            })).unwrap_or(theme.font_size_medium)
-        }
+    /// Get the Color of mines.
+    pub fn mine_color(&self) -> Color {
+        self.maybe_mine_color.unwrap_or(color::rgb(0.9, 0.2, 0.2))
     }
 
-    /// Provide the chainable color() configuration method.
-    impl<'a, F> Colorable for Cell<'a, F> {
-        fn color(mut self, color: Color) -> Self {
-            self.style.maybe_color = Some(color);
-            self
-        }
+    /// Get the label Color.
+    pub fn label_color(&self, theme: &Theme) -> Color {
+        self.maybe_label_color.or(theme.widget_style::<Self>(KIND).map(|default| {
+            default.style.maybe_label_color.unwrap_or(theme.label_color)
+        })).unwrap_or(theme.label_color)
     }
 
-    /// Provide the chainable label(), label_color(), and label_font_size()
-    /// configuration methods.
-    impl<'a, F> Labelable<'a> for Cell<'a, F> {
-        fn label(mut self, text: &'a str) -> Self {
-            self.maybe_label = Some(text);
-            self
-        }
-        fn label_color(mut self, color: Color) -> Self {
-            self.style.maybe_label_color = Some(color);
-            self
-        }
-        fn label_font_size(mut self, size: FontSize) -> Self {
-            self.style.maybe_label_font_size = Some(size);
-            self
-        }
+    /// Get the label font size.
+    pub fn label_font_size(&self, theme: &Theme) -> FontSize {
+        self.maybe_label_font_size.or(theme.widget_style::<Self>(KIND).map(|default| {
+            default.style.maybe_label_font_size.unwrap_or(theme.font_size_medium)
+        })).unwrap_or(theme.font_size_medium)
     }
-// }
+}
@@ -0,0 +1,645 @@
+//! The command line. Without a command the game opens on its main menu; the commands open it on
+//! a board of their own, play in a terminal or without any screen at all, make and study
+//! boards, and report on past games, so that the game can be scripted. `USAGE` lists them.
+//!
+//! Errors are printed to standard error, and the exit status tells scripts how things went:
+//! see `SUCCESS` and the codes after it.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use std::slice;
+use std::str::FromStr;
+
+use minesweeper::{minefield, solver, stats};
+use minesweeper::coord::Coord;
+use minesweeper::game::{millis, Action, FirstClick, Game, Preset, Status};
+use minesweeper::render::Renderer;
+use minesweeper::replay::Replay;
+use minesweeper::settings::Settings;
+use minesweeper::stats::Stats;
+use minesweeper::topology::Topology;
+use minesweeper::world::World;
+
+use tui;
+
+pub const USAGE: &'static str = "\
+Usage: MineSweeper [COMMAND] [OPTIONS]
+
+Without a command the game opens on its main menu.
+
+Commands:
+  play [BOARD]              open the game on a new board
+  tui [BOARD]               play in the terminal
+  headless [BOARD] [--replay FILE]
+                            play the moves read from standard input, one a line, such as
+                            `open 3 4` or `flag 5 4`, then print the board
+  generate [BOARD] [--count N] [--no-guess [--first-click any|safe|opening]] OUTPUT
+                            write boards to the file OUTPUT, or for more than one, into
+                            the directory OUTPUT; with --no-guess, only boards that can be
+                            cleared without guessing, first clicks protected as the
+                            settings or --first-click say
+  solve FILE [--start COLUMN,ROW[,LAYER]]
+                            tell whether a board written by `generate` can be cleared
+                            without guessing; FILE may be - for standard input
+  replay FILE [--steps] [--verify]
+                            play a replay back, move by move with --steps, and check
+                            that it's an honest win with --verify
+  show REPLAY|--seed N [BOARD] [--view player|solution|diff] [--rulers] [--unicode]
+       [--colour]           draw a replay's board or a dealt one
+  stats [--preset NAME] [--format text|csv|json] [--output FILE]
+                            summarise or export the games played
+  world [--seed N] [--density D] [--topology NAME]
+                            explore an endless board in the terminal
+  help                      this help
+
+BOARD is any of:
+  --preset NAME             beginner, intermediate, expert or cube
+  --width N, --height N, --depth N, --mines N
+                            a custom board, changing the preset's size
+  --topology NAME           square, hex, triangle, knight or mask:..., with -torus
+                            added to wrap around
+  --seed N                  deal the same board every time
+
+Environment:
+  MINESWEEPER_ASSETS        a directory of fonts and skins, laid out like the game's
+                            assets, to use instead of the built-in ones
+
+Exit status: 0 on success, 1 when something went wrong, 2 for bad arguments, 3 when a
+headless game was lost and 4 when a headless game or a solve didn't clear the board.
+";
+
+pub const SUCCESS: i32 = 0;
+/// A file couldn't be read or written, didn't make sense, or a replay didn't verify.
+pub const FAILURE: i32 = 1;
+/// The command line was wrong.
+pub const BAD_USAGE: i32 = 2;
+/// A headless game hit a mine.
+pub const LOST: i32 = 3;
+/// A headless game ended before the board was cleared, or the solver needed a guess.
+pub const UNFINISHED: i32 = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The command line was wrong.
+    Usage(String),
+    Failed(String),
+}
+
+impl Error {
+    /// The exit status to leave with.
+    pub fn code(&self) -> i32 {
+        match *self {
+            Error::Usage(_) => BAD_USAGE,
+            Error::Failed(_) => FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Usage(ref e) => write!(f, "{}\nRun `MineSweeper help` for usage.", e),
+            Error::Failed(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(e: String) -> Error {
+        Error::Failed(e)
+    }
+}
+
+/// What `main` does once the command line is read.
+pub enum Next {
+    /// Open the window, on a game if the command dealt one.
+    Gui(Option<Game>),
+    Exit(i32),
+}
+
+pub fn run(args: &[String]) -> Result<Next, Error> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => return Ok(Next::Gui(None)),
+    };
+    let code = match command {
+        "play" => return Ok(Next::Gui(Some(try!(try!(GameOptions::parse(args)).deal())))),
+        "tui" => {
+            let game = try!(try!(GameOptions::parse(args)).deal());
+            try!(tui::run(game).map_err(|e| e.to_string()));
+            SUCCESS
+        },
+        "headless" => try!(headless(args)),
+        "generate" => try!(generate(args)),
+        "solve" => try!(solve(args)),
+        "replay" => try!(replay(args)),
+        "show" => try!(show(args)),
+        "stats" => try!(stats(args)),
+        "world" => try!(world(args)),
+        "help" | "--help" | "-h" => {
+            print!("{}", USAGE);
+            SUCCESS
+        },
+        _ => return Err(Error::Usage(format!("unknown command: {}", command))),
+    };
+    Ok(Next::Exit(code))
+}
+
+/// The value after the option `arg`.
+fn value<'a>(arg: &str, args: &mut slice::Iter<'a, String>) -> Result<&'a str, Error> {
+    args.next().map(|value| value.as_str())
+        .ok_or(Error::Usage(format!("missing value for {}", arg)))
+}
+
+fn number<T: FromStr>(arg: &str, value: &str) -> Result<T, Error> {
+    value.parse().map_err(|_| Error::Usage(format!("{} needs a whole number, not {}", arg, value)))
+}
+
+fn unknown(arg: &str) -> Error {
+    Error::Usage(format!("unknown argument: {}", arg))
+}
+
+fn read(path: &str) -> Result<String, Error> {
+    let mut text = String::new();
+    let result = if path == "-" {
+        io::stdin().read_to_string(&mut text)
+    } else {
+        File::open(path).and_then(|mut file| file.read_to_string(&mut text))
+    };
+    try!(result.map_err(|e| format!("couldn't read {}: {}", path, e)));
+    Ok(text)
+}
+
+fn write(path: &Path, text: &str) -> Result<(), Error> {
+    try!(File::create(path).and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|e| format!("couldn't write {}: {}", path.display(), e)));
+    Ok(())
+}
+
+/// The board a command deals: a preset, or a custom size, on a topology, from a seed.
+#[derive(Clone, Debug, Default)]
+struct GameOptions {
+    preset: Option<Preset>,
+    width: Option<usize>,
+    height: Option<usize>,
+    depth: Option<usize>,
+    mines: Option<usize>,
+    topology: Topology,
+    seed: Option<u64>,
+}
+
+impl GameOptions {
+    /// Options made only of board options.
+    fn parse(args: &[String]) -> Result<GameOptions, Error> {
+        let mut options = GameOptions::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !try!(options.take(arg, &mut args)) {
+                return Err(unknown(arg));
+            }
+        }
+        Ok(options)
+    }
+
+    /// Read `arg` and its value if it is a board option, and say whether it was.
+    fn take(&mut self, arg: &str, args: &mut slice::Iter<String>) -> Result<bool, Error> {
+        match arg {
+            "--preset" => {
+                let name = try!(value(arg, args));
+                self.preset = Some(try!(Preset::standard(name).ok_or_else(|| {
+                    Error::Usage(format!("unknown preset: {} (expected beginner, intermediate, \
+                                          expert or cube)", name))
+                })));
+            },
+            "--width" => self.width = Some(try!(number(arg, try!(value(arg, args))))),
+            "--height" => self.height = Some(try!(number(arg, try!(value(arg, args))))),
+            "--depth" => self.depth = Some(try!(number(arg, try!(value(arg, args))))),
+            "--mines" => self.mines = Some(try!(number(arg, try!(value(arg, args))))),
+            "--topology" => {
+                self.topology = try!(Topology::from_name(try!(value(arg, args)))
+                    .map_err(Error::Usage));
+            },
+            "--seed" => self.seed = Some(try!(number(arg, try!(value(arg, args))))),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// The board asked for. Any size or mine count makes a custom board, the rest of its size
+    /// taken from the preset, or from beginner.
+    fn preset(&self) -> Result<Preset, Error> {
+        let preset = self.preset.unwrap_or(Preset::Beginner);
+        let (width, height, mines) = preset.dimensions();
+        let preset = match (self.width, self.height, self.depth, self.mines) {
+            (None, None, None, None) => preset,
+            _ => {
+                let width = self.width.unwrap_or(width);
+                let height = self.height.unwrap_or(height);
+                let depth = self.depth.unwrap_or(preset.depth());
+                let mines = self.mines.unwrap_or(mines);
+                let preset = Preset::Custom {
+                    width: width,
+                    height: height,
+                    depth: depth,
+                    mines: mines,
+                };
+                try!(preset.check().map_err(Error::Usage));
+                preset
+            },
+        };
+        let (width, height, _) = preset.dimensions();
+        try!(self.topology.check(width, height).map_err(Error::Usage));
+        Ok(preset)
+    }
+
+    /// A game on the board asked for, from the seed given or a random one.
+    fn deal(&self) -> Result<Game, Error> {
+        let preset = try!(self.preset());
+        Ok(match self.seed {
+            Some(seed) => Game::new(preset, self.topology, seed),
+            None => Game::random(preset, self.topology),
+        })
+    }
+}
+
+/// `headless`: play moves from standard input, then print the board. Exits with `SUCCESS` for
+/// a cleared board, `LOST` and `UNFINISHED` otherwise.
+fn headless(args: &[String]) -> Result<i32, Error> {
+    let mut options = GameOptions::default();
+    let mut replay_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if try!(options.take(arg, &mut args)) {
+            continue;
+        }
+        match arg.as_str() {
+            "--replay" => replay_path = Some(try!(value(arg, &mut args))),
+            _ => return Err(unknown(arg)),
+        }
+    }
+    let mut game = try!(options.deal());
+
+    let stdin = io::stdin();
+    for (number, line) in stdin.lock().lines().enumerate() {
+        let line = try!(line.map_err(|e| format!("couldn't read standard input: {}", e)));
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() || words[0].starts_with('#') {
+            continue;
+        }
+        let malformed = || {
+            Error::Failed(format!("line {}: expected `open|flag|chord COLUMN ROW [LAYER]`, \
+                                   counting from 1: {}", number + 1, line.trim()))
+        };
+        let action = match words[0] {
+            "o" => Action::Open,
+            "f" => Action::Flag,
+            "c" => Action::Chord,
+            word => try!(Action::from_name(word).ok_or_else(&malformed)),
+        };
+        let numbers: Vec<usize> = words[1..].iter().filter_map(|word| word.parse().ok())
+            .collect();
+        if numbers.len() != words.len() - 1 || numbers.len() < 2 || numbers.len() > 3 ||
+           numbers.contains(&0) {
+            return Err(malformed());
+        }
+        let layer = numbers.get(2).map_or(0, |n| n - 1);
+        let coord = Coord::new(numbers[0] - 1, numbers[1] - 1, layer);
+        if !game.map.size().contains(coord) {
+            return Err(Error::Failed(format!("line {}: {} {} {} is off the board", number + 1,
+                                             coord.column + 1, coord.row + 1, coord.layer + 1)));
+        }
+        match action {
+            Action::Open => game.open(coord),
+            Action::Flag => game.toggle_flag(coord),
+            Action::Chord => game.chord(coord),
+        }
+    }
+
+    if let Some(path) = replay_path {
+        try!(write(Path::new(path), &Replay::from_game(&game).to_string()));
+    }
+    print!("{}", Renderer::new().player(&game.board()));
+    let board = game.board();
+    println!("{} after {} moves, {} mines left", status_name(&game), game.moves.len(),
+             board.mines_left());
+    Ok(match game.status {
+        Status::Finished => SUCCESS,
+        Status::Failed => LOST,
+        Status::Playing => UNFINISHED,
+    })
+}
+
+fn status_name(game: &Game) -> &'static str {
+    match game.status {
+        Status::Playing => "playing",
+        Status::Failed => "lost",
+        Status::Finished => "cleared",
+    }
+}
+
+/// `generate`: write boards, numbering them when there are several.
+fn generate(args: &[String]) -> Result<i32, Error> {
+    // How many seeds to try for each board without guesses before giving up.
+    const ATTEMPTS: usize = 10000;
+
+    let mut options = GameOptions::default();
+    let mut count = 1;
+    let mut no_guess = false;
+    let mut first_click = None;
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if try!(options.take(arg, &mut args)) {
+            continue;
+        }
+        match arg.as_str() {
+            "--count" => count = try!(number(arg, try!(value(arg, &mut args)))),
+            "--no-guess" => no_guess = true,
+            "--first-click" => {
+                let name = try!(value(arg, &mut args));
+                first_click = Some(try!(FirstClick::from_name(name).ok_or_else(|| {
+                    Error::Usage(format!("unknown first click policy: {} (expected any, safe or \
+                                          opening)", name))
+                })));
+            },
+            path if output.is_none() && !path.starts_with("--") => output = Some(path),
+            _ => return Err(unknown(arg)),
+        }
+    }
+    let output = Path::new(try!(output.ok_or(Error::Usage("give a file to write".to_owned()))));
+    if count == 0 {
+        return Err(Error::Usage("--count needs to be at least 1".to_owned()));
+    }
+    if first_click.is_some() && !no_guess {
+        return Err(Error::Usage("--first-click only applies with --no-guess".to_owned()));
+    }
+    // Boards are judged as the game would deal them to the player, so by the settings' policy.
+    let first_click = match first_click {
+        Some(first_click) => first_click,
+        None if no_guess => {
+            Settings::load().map(|(settings, _)| settings.first_click).unwrap_or(FirstClick::Any)
+        },
+        None => FirstClick::Any,
+    };
+    if count > 1 {
+        try!(fs::create_dir_all(output)
+            .map_err(|e| format!("couldn't create {}: {}", output.display(), e)));
+    }
+
+    let digits = count.to_string().len();
+    for i in 0..count {
+        // A given seed starts a run of seeds, one for each board written or tried.
+        let mut game = try!(options.deal());
+        let mut start = None;
+        let mut attempts = 1;
+        while no_guess {
+            start = start_without_guessing(&mut game, first_click);
+            if start.is_some() {
+                break;
+            }
+            if attempts == ATTEMPTS {
+                return Err(Error::Failed(format!("found no board without guesses in {} tries",
+                                                 ATTEMPTS)));
+            }
+            attempts += 1;
+            options.seed = options.seed.map(|seed| seed.wrapping_add(1));
+            game = try!(options.deal());
+        }
+        options.seed = options.seed.map(|seed| seed.wrapping_add(1));
+
+        let path = if count > 1 {
+            output.join(format!("board-{:01$}.txt", i + 1, digits))
+        } else {
+            output.to_owned()
+        };
+        let mut comment = format!("{} {}, seed {}", game.preset.standard_name(),
+                                  game.topology.name(), game.seed);
+        if let Some(start) = start {
+            comment.push_str(&format!(", start {},{},{}", start.column + 1, start.row + 1,
+                                      start.layer + 1));
+        }
+        try!(write(&path, &minefield::to_text(&game.map, Some(&comment))));
+        println!("{}", path.display());
+    }
+    Ok(SUCCESS)
+}
+
+/// Open the cell a player would start `game` on, with mines moved away from it as `first_click`
+/// says, and give it back if the board can be cleared from there without guessing. Unprotected,
+/// that's the cell `solver::default_start` picks; protected, any cell will do, so the middle of
+/// the top layer.
+fn start_without_guessing(game: &mut Game, first_click: FirstClick) -> Option<Coord> {
+    let start = match first_click {
+        FirstClick::Any => solver::default_start(&game.map).unwrap(),
+        _ => Coord::new(game.map.width / 2, game.map.height / 2, 0),
+    };
+    game.first_click = first_click;
+    game.open(start);
+    if solver::analyse(&game.map, start).is_cleared() {
+        Some(start)
+    } else {
+        None
+    }
+}
+
+/// `solve`: how far a board can be cleared without guessing. Exits with `UNFINISHED` if not
+/// all the way.
+fn solve(args: &[String]) -> Result<i32, Error> {
+    let mut path = None;
+    let mut start = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--start" => {
+                let value = try!(value(arg, &mut args));
+                let numbers: Vec<usize> = value.split(',').filter_map(|n| n.parse().ok())
+                    .collect();
+                if numbers.len() != value.split(',').count() || numbers.len() < 2 ||
+                   numbers.len() > 3 || numbers.contains(&0) {
+                    return Err(Error::Usage(format!("--start needs COLUMN,ROW or \
+                                                     COLUMN,ROW,LAYER counting from 1, not {}",
+                                                    value)));
+                }
+                start = Some(Coord::new(numbers[0] - 1, numbers[1] - 1,
+                                        numbers.get(2).map_or(0, |n| n - 1)));
+            },
+            "-" => path = Some("-"),
+            name if path.is_none() && !name.starts_with("--") => path = Some(name),
+            _ => return Err(unknown(arg)),
+        }
+    }
+    let path = try!(path.ok_or(Error::Usage("give a board file to solve".to_owned())));
+    let map = try!(minefield::parse(&try!(read(path))).map_err(|e| format!("{}: {}", path, e)));
+
+    let start = match start {
+        Some(start) if !map.size().contains(start) => {
+            return Err(Error::Usage(format!("--start {},{},{} is off the board",
+                                            start.column + 1, start.row + 1, start.layer + 1)));
+        },
+        Some(start) => start,
+        None => solver::default_start(&map).unwrap(),
+    };
+    if map.is_mine(start) {
+        return Err(Error::Failed(format!("the start, {},{},{}, holds a mine", start.column + 1,
+                                         start.row + 1, start.layer + 1)));
+    }
+    let analysis = solver::analyse(&map, start);
+    print!("{}", analysis);
+    Ok(if analysis.is_cleared() { SUCCESS } else { UNFINISHED })
+}
+
+/// `replay`: play a replay file back and show the board it leaves.
+fn replay(args: &[String]) -> Result<i32, Error> {
+    let mut path = None;
+    let mut steps = false;
+    let mut verify = false;
+    for arg in args {
+        match arg.as_str() {
+            "--steps" => steps = true,
+            "--verify" => verify = true,
+            name if path.is_none() && !name.starts_with("--") => path = Some(name),
+            _ => return Err(unknown(arg)),
+        }
+    }
+    let path = try!(path.ok_or(Error::Usage("give a replay file".to_owned())));
+    let replay = try!(Replay::parse(&try!(read(path))).map_err(|e| format!("{}: {}", path, e)));
+
+    let renderer = Renderer::new().rulers(true);
+    if steps {
+        let mut game = Replay { moves: vec![], ..replay.clone() }.play();
+        for (i, &step) in replay.moves.iter().enumerate() {
+            game.apply(step);
+            println!("move {}: {} {} {} {} {}", i + 1, stats::format_time(step.time_ms),
+                     step.action.name(), step.coord.column + 1, step.coord.row + 1,
+                     step.coord.layer + 1);
+            print!("{}", renderer.player(&game.board()));
+        }
+    }
+    let game = replay.play();
+    if !steps {
+        print!("{}", renderer.player(&game.board()));
+    }
+    println!("{} {} on seed {}: {} after {} moves in {}", game.preset.standard_name(),
+             game.topology.name(), game.seed, status_name(&game), game.moves.len(),
+             stats::format_time(millis(game.elapsed())));
+
+    if verify {
+        let time = try!(replay.verify().map_err(|e| format!("{} doesn't verify: {}", path, e)));
+        println!("verified: cleared in {}", stats::format_time(time));
+    }
+    Ok(SUCCESS)
+}
+
+/// `show`: draw the board a replay leaves, or one dealt from a seed.
+fn show(args: &[String]) -> Result<i32, Error> {
+    let mut options = GameOptions::default();
+    let mut renderer = Renderer::new();
+    let mut view = "player";
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if try!(options.take(arg, &mut args)) {
+            continue;
+        }
+        match arg.as_str() {
+            "--view" => view = match try!(value(arg, &mut args)) {
+                name @ "player" | name @ "solution" | name @ "diff" => name,
+                name => return Err(Error::Usage(format!("unknown view: {} (expected player, \
+                                                         solution or diff)", name))),
+            },
+            "--rulers" => renderer = renderer.rulers(true),
+            "--unicode" => renderer = renderer.unicode(true),
+            "--colour" | "--color" => renderer = renderer.colour(true),
+            name if path.is_none() && !name.starts_with("--") => path = Some(name),
+            _ => return Err(unknown(arg)),
+        }
+    }
+    let game = match (path, options.seed) {
+        (Some(path), None) => {
+            try!(Replay::parse(&try!(read(path))).map_err(|e| format!("{}: {}", path, e))).play()
+        },
+        (None, Some(_)) => try!(options.deal()),
+        _ => return Err(Error::Usage("give either a replay file or --seed".to_owned())),
+    };
+    print!("{}", match view {
+        "solution" => renderer.solution(&game.map),
+        "diff" => renderer.diff(&game.map),
+        _ => renderer.player(&game.board()),
+    });
+    Ok(SUCCESS)
+}
+
+/// `stats`: summarise the games played, or export them all.
+fn stats(args: &[String]) -> Result<i32, Error> {
+    let mut preset = None;
+    let mut format = "text";
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--preset" => preset = Some(try!(value(arg, &mut args))),
+            "--format" => format = match try!(value(arg, &mut args)) {
+                name @ "text" | name @ "csv" | name @ "json" => name,
+                name => return Err(Error::Usage(format!("unknown format: {} (expected text, \
+                                                         csv or json)", name))),
+            },
+            "--output" => output = Some(try!(value(arg, &mut args))),
+            _ => return Err(unknown(arg)),
+        }
+    }
+    let history = try!(Stats::load().map_err(|e| format!("couldn't read statistics: {}", e)));
+    let text = match format {
+        "csv" => stats::to_csv(&history.filter(preset)),
+        "json" => stats::to_json(&history.filter(preset)),
+        _ => stats::to_text(&history.summary(preset)),
+    };
+    match output {
+        Some(path) => try!(write(Path::new(path), &text)),
+        None => print!("{}", text),
+    }
+    Ok(SUCCESS)
+}
+
+/// `world`: continue the saved world unless any option is given, which starts a new one over
+/// it.
+fn world(args: &[String]) -> Result<i32, Error> {
+    use rand::{self, Rng};
+
+    let path = try!(World::default_path().ok_or("couldn't find a data directory".to_owned()));
+    let mut seed = rand::thread_rng().gen::<u64>();
+    let mut density = 0.15;
+    let mut topology = Topology::default();
+
+    if args.is_empty() {
+        match World::load(&path) {
+            Ok(world) => {
+                try!(tui::run_world(world, &path).map_err(|e| e.to_string()));
+                return Ok(SUCCESS);
+            },
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(Error::Failed(format!("couldn't load {}: {}", path.display(), e))),
+        }
+    }
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = try!(number(arg, try!(value(arg, &mut args)))),
+            "--density" => {
+                let value = try!(value(arg, &mut args));
+                density = try!(value.parse::<f64>().map_err(|_| {
+                    Error::Usage(format!("--density needs a number, not {}", value))
+                }));
+            },
+            "--topology" => {
+                topology = try!(Topology::from_name(try!(value(arg, &mut args)))
+                    .map_err(Error::Usage));
+            },
+            _ => return Err(unknown(arg)),
+        }
+    }
+    let world = try!(World::new(seed, density, topology).map_err(Error::Usage));
+    try!(tui::run_world(world, &path).map_err(|e| e.to_string()));
+    Ok(SUCCESS)
+}
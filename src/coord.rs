@@ -0,0 +1,139 @@
+//! Positions on a board and the order cells are stored in.
+//!
+//! Cells are kept layer by layer, row by row: `Size::index` and `Size::coord` convert between a
+//! `Coord` and that position, so nothing else needs to do the arithmetic.
+
+use std::cmp;
+use std::fmt;
+
+/// A cell's position: its column, row and layer, all counted from 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coord {
+    pub column: usize,
+    pub row: usize,
+    pub layer: usize,
+}
+
+impl Coord {
+    pub fn new(column: usize, row: usize, layer: usize) -> Coord {
+        Coord {
+            column: column,
+            row: row,
+            layer: layer,
+        }
+    }
+
+    /// A cell on the first (or only) layer.
+    pub fn flat(column: usize, row: usize) -> Coord {
+        Coord::new(column, row, 0)
+    }
+
+    /// The same column and row on another layer.
+    pub fn on_layer(&self, layer: usize) -> Coord {
+        Coord::new(self.column, self.row, layer)
+    }
+}
+
+/// `column,row`, followed by `,layer` off the first layer.
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.layer == 0 {
+            write!(f, "{},{}", self.column, self.row)
+        } else {
+            write!(f, "{},{},{}", self.column, self.row, self.layer)
+        }
+    }
+}
+
+/// The dimensions of a board.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Size {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+}
+
+impl Size {
+    pub fn new(width: usize, height: usize, depth: usize) -> Size {
+        Size {
+            width: width,
+            height: height,
+            depth: depth,
+        }
+    }
+
+    /// The number of cells.
+    pub fn len(&self) -> usize {
+        self.width * self.height * self.depth
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.column < self.width && coord.row < self.height && coord.layer < self.depth
+    }
+
+    /// Where the cell at `coord` is stored.
+    pub fn index(&self, coord: Coord) -> usize {
+        (coord.layer * self.height + coord.row) * self.width + coord.column
+    }
+
+    /// The cell stored at `index`.
+    pub fn coord(&self, index: usize) -> Coord {
+        Coord::new(index % self.width, index / self.width % self.height,
+                   index / (self.width * self.height))
+    }
+
+    /// Every cell, in the order they are stored.
+    pub fn coords(&self) -> Coords {
+        Coords::new(Coord::new(0, 0, 0), Coord::new(self.width, self.height, self.depth))
+    }
+
+    /// The cells in the box between `from` and `to`, both included, cut to the board.
+    pub fn region(&self, from: Coord, to: Coord) -> Coords {
+        Coords::new(from, Coord::new(cmp::min(to.column + 1, self.width),
+                                     cmp::min(to.row + 1, self.height),
+                                     cmp::min(to.layer + 1, self.depth)))
+    }
+}
+
+/// Walks a box of cells layer by layer, row by row.
+#[derive(Clone, Debug)]
+pub struct Coords {
+    from: Coord,
+    /// One past the last column, row and layer.
+    end: Coord,
+    next: Option<Coord>,
+}
+
+impl Coords {
+    fn new(from: Coord, end: Coord) -> Coords {
+        let empty = from.column >= end.column || from.row >= end.row || from.layer >= end.layer;
+        Coords {
+            from: from,
+            end: end,
+            next: if empty { None } else { Some(from) },
+        }
+    }
+}
+
+impl Iterator for Coords {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Coord> {
+        let current = match self.next {
+            Some(current) => current,
+            None => return None,
+        };
+        let mut next = current;
+        next.column += 1;
+        if next.column == self.end.column {
+            next.column = self.from.column;
+            next.row += 1;
+            if next.row == self.end.row {
+                next.row = self.from.row;
+                next.layer += 1;
+            }
+        }
+        self.next = if next.layer == self.end.layer { None } else { Some(next) };
+        Some(current)
+    }
+}
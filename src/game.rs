@@ -0,0 +1,390 @@
+//! A single round of Minesweeper: the map plus everything needed to score it.
+
+use std::time::{Duration, Instant};
+
+use board::Board;
+use coord::Coord;
+use map::{Interaction, Map};
+use topology::Topology;
+
+/// The standard difficulties, plus whatever the player sets up by hand.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Preset {
+    Beginner,
+    Intermediate,
+    Expert,
+    /// A board of any size. Only custom boards can have more than one layer.
+    Custom { width: usize, height: usize, depth: usize, mines: usize },
+}
+
+impl Preset {
+    /// Board width, height and mine count.
+    pub fn dimensions(&self) -> (usize, usize, usize) {
+        match *self {
+            Preset::Beginner => (9, 9, 10),
+            Preset::Intermediate => (16, 16, 40),
+            Preset::Expert => (30, 16, 99),
+            Preset::Custom { width, height, mines, .. } => (width, height, mines),
+        }
+    }
+
+    /// Number of layers.
+    pub fn depth(&self) -> usize {
+        match *self {
+            Preset::Custom { depth, .. } => depth,
+            _ => 1,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Preset::Beginner => "beginner",
+            Preset::Intermediate => "intermediate",
+            Preset::Expert => "expert",
+            Preset::Custom { .. } => "custom",
+        }
+    }
+
+    /// Look a preset up by the name returned from `name`. A custom preset is rebuilt from the
+    /// dimensions given.
+    pub fn from_name(name: &str, width: usize, height: usize, depth: usize,
+                     mines: usize) -> Option<Preset> {
+        match name {
+            "beginner" => Some(Preset::Beginner),
+            "intermediate" => Some(Preset::Intermediate),
+            "expert" => Some(Preset::Expert),
+            "custom" => Some(Preset::Custom {
+                width: width,
+                height: height,
+                depth: depth,
+                mines: mines,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The presets players pick by name: the three difficulties and the cube.
+    pub fn standard(name: &str) -> Option<Preset> {
+        match name {
+            "beginner" => Some(Preset::Beginner),
+            "intermediate" => Some(Preset::Intermediate),
+            "expert" => Some(Preset::Expert),
+            "cube" => Some(CUBE),
+            _ => None,
+        }
+    }
+
+    /// The name `standard` knows the preset by. The cube is a custom board, but has a name of
+    /// its own.
+    pub fn standard_name(&self) -> &'static str {
+        if *self == CUBE { "cube" } else { self.name() }
+    }
+
+    /// Check that a board can be dealt for the preset: it has cells, no more than `MAX_CELLS`
+    /// of them, and room for at least one without a mine. Dimensions read from outside, such as
+    /// from a replay or the command line, go through this before any board is built.
+    pub fn check(&self) -> Result<(), String> {
+        let (width, height, mines) = self.dimensions();
+        let cells = width.checked_mul(height).and_then(|cells| cells.checked_mul(self.depth()));
+        match cells {
+            Some(0) => Err("the board must have at least one cell".to_owned()),
+            Some(cells) if cells <= MAX_CELLS => if mines < cells {
+                Ok(())
+            } else {
+                Err(format!("{} mines don't fit on {} cells", mines, cells))
+            },
+            _ => Err(format!("boards can have at most {} cells", MAX_CELLS)),
+        }
+    }
+}
+
+/// A duration in whole milliseconds, the unit game times are kept in.
+pub fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + (duration.subsec_nanos() / 1_000_000) as u64
+}
+
+/// The most cells a board may have.
+pub const MAX_CELLS: usize = 1 << 20;
+
+/// A three dimensional board: six layers of six by six.
+pub const CUBE: Preset = Preset::Custom { width: 6, height: 6, depth: 6, mines: 20 };
+
+/// Something the player did to a cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Open,
+    Flag,
+    Chord,
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Action::Open => "open",
+            Action::Flag => "flag",
+            Action::Chord => "chord",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        match name {
+            "open" => Some(Action::Open),
+            "flag" => Some(Action::Flag),
+            "chord" => Some(Action::Chord),
+            _ => None,
+        }
+    }
+}
+
+/// What the first cell opened is allowed to hold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FirstClick {
+    /// Whatever the seed put there, mines included.
+    Any,
+    /// Never a mine: a mine there is moved elsewhere.
+    Safe,
+    /// No mine there or around it, so the first click always opens an area.
+    Opening,
+}
+
+impl FirstClick {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            FirstClick::Any => "any",
+            FirstClick::Safe => "safe",
+            FirstClick::Opening => "opening",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<FirstClick> {
+        match name {
+            "any" => Some(FirstClick::Any),
+            "safe" => Some(FirstClick::Safe),
+            "opening" => Some(FirstClick::Opening),
+            _ => None,
+        }
+    }
+}
+
+/// One step of a game, timed from the first click.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Move {
+    pub time_ms: u64,
+    pub action: Action,
+    pub coord: Coord,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    Playing,
+    Failed,
+    Finished,
+}
+
+pub struct Game {
+    pub map: Map,
+    pub preset: Preset,
+    pub topology: Topology,
+    pub seed: u64,
+    pub status: Status,
+    /// What the first cell opened may hold. Mines are moved away from it, the same way every
+    /// time, so replays need to know.
+    pub first_click: FirstClick,
+    /// Whether flagging a flag turns it into a question mark rather than taking it off.
+    pub question_marks: bool,
+    /// Every reveal, flag and chord made while playing.
+    pub clicks: usize,
+    /// Everything the player has done so far, in order.
+    pub moves: Vec<Move>,
+    started: Option<Instant>,
+    stopped: Option<Duration>,
+    /// When the game was paused, if it is.
+    paused: Option<Instant>,
+}
+
+impl Game {
+    pub fn new(preset: Preset, topology: Topology, seed: u64) -> Game {
+        let (width, height, mines) = preset.dimensions();
+        let mut map: Map = Default::default();
+        map.topology = topology;
+        map.populate_seeded(width, height, preset.depth(), mines, seed);
+
+        Game {
+            map: map,
+            preset: preset,
+            topology: topology,
+            seed: seed,
+            status: Status::Playing,
+            first_click: FirstClick::Any,
+            question_marks: false,
+            clicks: 0,
+            moves: vec![],
+            started: None,
+            stopped: None,
+            paused: None,
+        }
+    }
+
+    /// Start a game on a freshly drawn random seed.
+    pub fn random(preset: Preset, topology: Topology) -> Game {
+        use rand::{self, Rng};
+
+        Game::new(preset, topology, rand::thread_rng().gen::<u64>())
+    }
+
+    /// What the player may see of the game.
+    pub fn board(&self) -> Board {
+        Board::from_game(self)
+    }
+
+    /// Open a cell. The first cell opened is protected as `first_click` says, whatever was
+    /// flagged or chorded before it. Clicking a cell that can't be opened, such as a flag, does
+    /// nothing and isn't a move.
+    pub fn open(&mut self, coord: Coord) {
+        if !self.map.size().contains(coord) || !self.map.cell(coord).is_closed() {
+            return;
+        }
+        let first = !self.map.cells().any(|(_, cell)| cell.interaction() == Interaction::Opened);
+        if first && !self.is_over() && !self.is_paused() {
+            self.clear_first_click(coord);
+        }
+        if self.begin_move(Action::Open, coord) {
+            let exploded = self.map.open(coord);
+            self.end_move(exploded);
+        }
+    }
+
+    pub fn toggle_flag(&mut self, coord: Coord) {
+        if self.begin_move(Action::Flag, coord) {
+            if self.question_marks {
+                self.map.cycle_mark(coord);
+            } else {
+                self.map.toggle_flag(coord);
+            }
+        }
+    }
+
+    pub fn chord(&mut self, coord: Coord) {
+        if self.begin_move(Action::Chord, coord) {
+            let exploded = self.map.chord(coord);
+            self.end_move(exploded);
+        }
+    }
+
+    /// Apply a recorded move. Unlike the other actions the clock isn't consulted: the move
+    /// carries its own time, which becomes the game time if it ends the game.
+    pub fn apply(&mut self, step: Move) {
+        let before = self.moves.len();
+        match step.action {
+            Action::Open => self.open(step.coord),
+            Action::Flag => self.toggle_flag(step.coord),
+            Action::Chord => self.chord(step.coord),
+        }
+        if self.moves.len() == before {
+            return;
+        }
+        if let Some(last) = self.moves.last_mut() {
+            last.time_ms = step.time_ms;
+        }
+        if self.is_over() {
+            self.stopped = Some(Duration::from_millis(step.time_ms));
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.status != Status::Playing
+    }
+
+    /// Time spent playing. The clock starts with the first click and stops when the game ends,
+    /// and while it is paused.
+    pub fn elapsed(&self) -> Duration {
+        match (self.stopped, self.started) {
+            (Some(stopped), _) => stopped,
+            (None, Some(started)) => self.paused.unwrap_or_else(Instant::now) - started,
+            (None, None) => Duration::from_secs(0),
+        }
+    }
+
+    /// Stop the clock. Nothing can be done to the board until the game is resumed.
+    pub fn pause(&mut self) {
+        if self.paused.is_none() {
+            self.paused = Some(Instant::now());
+        }
+    }
+
+    /// Start the clock again, from where it was paused.
+    pub fn resume(&mut self) {
+        if let Some(paused) = self.paused.take() {
+            self.started = self.started.map(|started| started + paused.elapsed());
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Move mines away from the first cell opened, as `first_click` asks. Boards too crowded
+    /// to clear an opening at least keep the cell itself safe.
+    fn clear_first_click(&mut self, coord: Coord) {
+        let mut cells = vec![coord];
+        match self.first_click {
+            FirstClick::Any => return,
+            FirstClick::Safe => (),
+            FirstClick::Opening => cells.extend(self.map.neighbours(coord)),
+        }
+        if !self.map.move_mines_from(&cells) {
+            self.map.move_mines_from(&[coord]);
+        }
+    }
+
+    fn begin_move(&mut self, action: Action, coord: Coord) -> bool {
+        if self.is_over() || self.is_paused() || !self.map.size().contains(coord) {
+            return false;
+        }
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+        self.clicks += 1;
+        self.moves.push(Move {
+            time_ms: millis(self.elapsed()),
+            action: action,
+            coord: coord,
+        });
+        true
+    }
+
+    fn end_move(&mut self, exploded: bool) {
+        if exploded {
+            self.status = Status::Failed;
+        } else if self.map.is_cleared() {
+            self.status = Status::Finished;
+        } else {
+            return;
+        }
+        self.stopped = Some(self.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, FirstClick, Game, Preset, Status};
+    use topology::Topology;
+
+    #[test]
+    fn clicking_a_flag_keeps_the_first_click_safe() {
+        let preset = Preset::Custom { width: 4, height: 4, depth: 1, mines: 6 };
+        let mut game = Game::new(preset, Topology::default(), 7);
+        game.first_click = FirstClick::Safe;
+        let mine = game.map.cells().find(|&(_, cell)| cell.is_mine()).unwrap().0;
+        let other = game.map.coords().find(|&coord| coord != mine).unwrap();
+
+        game.toggle_flag(other);
+        game.open(other);
+        assert_eq!(game.moves.len(), 1);
+        assert!(game.moves.iter().all(|step| step.action != Action::Open));
+
+        game.open(mine);
+        assert!(!game.map.is_mine(mine));
+        assert!(game.status != Status::Failed);
+    }
+}
@@ -0,0 +1,66 @@
+//! Where the parts of the window go. The layout is worked out from the window's size on every
+//! update, so it follows the window as it is resized: the sidebar of buttons and the minimap on
+//! the left, the header centred across the top and the board, with square cells, as large as
+//! fits in the rest.
+
+use conrod::{Dimensions, Point, Scalar};
+
+use minesweeper::board::Board;
+
+use board_view::{self, Layout, MIN_CELL_SIZE};
+
+/// The padding of the canvas around everything.
+pub const PAD: Scalar = 30.0;
+/// The width of the buttons, and of the sidebar holding them and the minimap.
+pub const SIDEBAR_WIDTH: Scalar = 180.0;
+/// Between the sidebar and the board.
+pub const GAP: Scalar = 20.0;
+/// The height of the header above the board.
+pub const HEADER_HEIGHT: Scalar = 60.0;
+/// How tall the sidebar gets on a layered board, with the minimap shown.
+const SIDEBAR_HEIGHT: Scalar = 440.0;
+/// Boards that would need a larger window than this are zoomed and panned instead.
+const MAX_WINDOW: Dimensions = [1600.0, 1000.0];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowLayout {
+    /// The space left for the board, right of the sidebar and under the header.
+    pub area: Dimensions,
+    /// The board widget: the largest board with square cells inside `area`.
+    pub board: Dimensions,
+    /// Where the board widget's top left corner is, from the canvas's top left corner inside
+    /// its padding.
+    pub board_offset: Point,
+}
+
+impl WindowLayout {
+    /// Lay out a window of `window` pixels. A window resized smaller than `min_window` is laid
+    /// out at that size and cut off, rather than squeezing the board and sidebar together.
+    pub fn new(window: Dimensions, board: &Board) -> WindowLayout {
+        let window = WindowLayout::clamp(window, board);
+        let area = [(window[0] - 2.0 * PAD - SIDEBAR_WIDTH - GAP).max(1.0),
+                    (window[1] - 2.0 * PAD - HEADER_HEIGHT).max(1.0)];
+        let fitted = board_view::fitted(board, area);
+        WindowLayout {
+            area: area,
+            board: fitted,
+            board_offset: [SIDEBAR_WIDTH + GAP + (area[0] - fitted[0]) / 2.0,
+                           HEADER_HEIGHT + (area[1] - fitted[1]) / 2.0],
+        }
+    }
+
+    /// The smallest window showing the whole of `board` with cells of at least
+    /// `MIN_CELL_SIZE`, and the sidebar.
+    pub fn min_window(board: &Board) -> Dimensions {
+        let (units_w, units_h) = Layout::units(board.topology.tiling, board.width, board.height);
+        let width = 2.0 * PAD + SIDEBAR_WIDTH + GAP + units_w * MIN_CELL_SIZE;
+        let height = 2.0 * PAD + HEADER_HEIGHT + (units_h * MIN_CELL_SIZE).max(SIDEBAR_HEIGHT);
+        [width.min(MAX_WINDOW[0]), height.min(MAX_WINDOW[1])]
+    }
+
+    /// `window`, made at least as large as `min_window` for `board`.
+    pub fn clamp(window: Dimensions, board: &Board) -> Dimensions {
+        let min = WindowLayout::min_window(board);
+        [window[0].max(min[0]), window[1].max(min[1])]
+    }
+}
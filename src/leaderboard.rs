@@ -0,0 +1,365 @@
+//! A small HTTP leaderboard for speed-running, and the client that submits wins to it.
+//!
+//! The server accepts a win only after re-playing it: clients `POST /scores` with the player's
+//! name in an `X-Player` header and a replay (see `replay`) as the body, and the server
+//! regenerates the board from the seed, checks the hash and runs every move before storing the
+//! time. `GET /scores` (optionally `?preset=expert&topology=hex`) lists the best times, one per
+//! line.
+//!
+//! Accepted scores are kept in a tab separated file next to the server.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use replay::Replay;
+use stats;
+
+/// How many times `GET /scores` lists at most.
+const TOP: usize = 20;
+/// Requests with bigger bodies are refused outright.
+const MAX_BODY: usize = 4 * 1024 * 1024;
+/// How many seconds either end of a connection waits on the other to connect, read or write.
+const TIMEOUT_SECS: u64 = 5;
+/// The least time between two moves a player could make by hand.
+const MIN_MOVE_MS: u64 = 40;
+
+/// An accepted, verified win.
+#[derive(Clone, Debug)]
+pub struct Score {
+    /// Seconds since the Unix epoch.
+    pub submitted: u64,
+    pub player: String,
+    pub preset: String,
+    pub topology: String,
+    pub seed: u64,
+    pub time_ms: u64,
+}
+
+pub struct Leaderboard {
+    pub scores: Vec<Score>,
+    path: PathBuf,
+}
+
+impl Leaderboard {
+    /// Load the scores kept at `path`. A missing file is an empty leaderboard.
+    pub fn open(path: &Path) -> io::Result<Leaderboard> {
+        let mut scores = vec![];
+        match File::open(path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = try!(line);
+                    // Scores stored before topologies existed have five fields and are square.
+                    let fields: Vec<&str> = line.split('\t').collect();
+                    if fields.len() != 5 && fields.len() != 6 {
+                        continue;
+                    }
+                    if let (Ok(submitted), Ok(seed), Ok(time_ms)) =
+                        (fields[0].parse(), fields[3].parse(), fields[4].parse()) {
+                        scores.push(Score {
+                            submitted: submitted,
+                            player: fields[1].to_owned(),
+                            preset: fields[2].to_owned(),
+                            topology: fields.get(5).unwrap_or(&"square").to_string(),
+                            seed: seed,
+                            time_ms: time_ms,
+                        });
+                    }
+                }
+            },
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+        Ok(Leaderboard {
+            scores: scores,
+            path: path.to_owned(),
+        })
+    }
+
+    /// Verify a submitted replay and, if it is an honest win, store its time. Moves must be at
+    /// least `MIN_MOVE_MS` apart, and each board can only be submitted once. Clients choose their
+    /// own seeds and write their own times, so these stop a careless bot or a resubmitted win,
+    /// not a board solved beforehand and played back at a human pace.
+    pub fn submit(&mut self, player: &str, replay: &Replay) -> Result<Score, String> {
+        let player = player.trim();
+        if player.is_empty() || player.len() > 32 || player.contains(|c: char| c.is_control()) {
+            return Err("player names must be 1 to 32 printable characters".to_owned());
+        }
+        let (preset, topology) = (replay.preset.name(), replay.topology.name());
+        if self.scores.iter().any(|score| {
+            score.seed == replay.seed && score.preset == preset && score.topology == topology
+        }) {
+            return Err(format!("seed {} has already been submitted for {} {}", replay.seed,
+                               preset, topology));
+        }
+        for (i, pair) in replay.moves.windows(2).enumerate() {
+            if pair[1].time_ms < pair[0].time_ms + MIN_MOVE_MS {
+                return Err(format!("move {} follows the one before too quickly", i + 2));
+            }
+        }
+        let time_ms = try!(replay.verify());
+
+        let score = Score {
+            submitted: SystemTime::now().duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            player: player.to_owned(),
+            preset: preset.to_owned(),
+            topology: topology,
+            seed: replay.seed,
+            time_ms: time_ms,
+        };
+        try!(self.append(&score).map_err(|e| format!("couldn't store the score: {}", e)));
+        self.scores.push(score.clone());
+        Ok(score)
+    }
+
+    /// The fastest scores, optionally only those for one preset and topology.
+    pub fn top(&self, preset: Option<&str>, topology: Option<&str>, count: usize) -> Vec<&Score> {
+        let mut scores: Vec<&Score> = self.scores.iter()
+            .filter(|score| preset.map_or(true, |preset| score.preset == preset))
+            .filter(|score| topology.map_or(true, |topology| score.topology == topology))
+            .collect();
+        scores.sort_by_key(|score| (score.time_ms, score.submitted));
+        scores.truncate(count);
+        scores
+    }
+
+    fn append(&self, score: &Score) -> io::Result<()> {
+        let mut file = try!(OpenOptions::new().create(true).append(true).open(&self.path));
+        writeln!(file, "{}\t{}\t{}\t{}\t{}\t{}", score.submitted, score.player, score.preset,
+                 score.seed, score.time_ms, score.topology)
+    }
+}
+
+/// A parsed HTTP request, only as much of it as the leaderboard needs.
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    player: Option<String>,
+    body: String,
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    try!(reader.read_line(&mut line).map_err(|e| e.to_string()));
+    let parts: Vec<&str> = line.trim().split(' ').collect();
+    if parts.len() != 3 {
+        return Err("malformed request line".to_owned());
+    }
+    let method = parts[0].to_owned();
+    let (path, query) = match parts[1].find('?') {
+        Some(i) => (parts[1][..i].to_owned(), parts[1][i + 1..].to_owned()),
+        None => (parts[1].to_owned(), String::new()),
+    };
+
+    let mut length = 0;
+    let mut player = None;
+    loop {
+        line.clear();
+        try!(reader.read_line(&mut line).map_err(|e| e.to_string()));
+        let header = line.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(i) = header.find(':') {
+            let value = header[i + 1..].trim();
+            match header[..i].to_lowercase().as_str() {
+                "content-length" => length = try!(value.parse::<usize>()
+                    .map_err(|_| "malformed Content-Length".to_owned())),
+                "x-player" => player = Some(value.to_owned()),
+                _ => (),
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err("request body too large".to_owned());
+    }
+
+    let mut body = vec![0; length];
+    try!(reader.read_exact(&mut body).map_err(|e| e.to_string()));
+    Ok(Request {
+        method: method,
+        path: path,
+        query: query,
+        player: player,
+        body: try!(String::from_utf8(body).map_err(|_| "body is not UTF-8".to_owned())),
+    })
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(stream, "HTTP/1.0 {}\r\nContent-Type: text/plain; charset=utf-8\r\n\
+                    Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+           status, body.len(), body)
+}
+
+fn handle(stream: &mut TcpStream, leaderboard: &mut Leaderboard) -> io::Result<()> {
+    let request = match read_request(stream) {
+        Ok(request) => request,
+        Err(e) => return write_response(stream, "400 Bad Request", &format!("{}\n", e)),
+    };
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/scores") => {
+            let param = |name: &str| {
+                let prefix = format!("{}=", name);
+                request.query.split('&')
+                    .find(|param| param.starts_with(&prefix))
+                    .map(|param| &param[prefix.len()..])
+            };
+            let top = leaderboard.top(param("preset"), param("topology"), TOP);
+            let body: String = top.iter().enumerate().map(|(i, score)| {
+                format!("{}\t{}\t{}\t{}\t{}\t{}\n", i + 1, score.player, score.preset,
+                        score.topology, stats::format_time(score.time_ms), score.seed)
+            }).collect();
+            write_response(stream, "200 OK", &body)
+        },
+        ("POST", "/scores") => {
+            let player = request.player.unwrap_or_default();
+            let result = Replay::parse(&request.body)
+                .and_then(|replay| leaderboard.submit(&player, &replay));
+            match result {
+                Ok(score) => {
+                    println!("accepted {} for {} on {} {}", stats::format_time(score.time_ms),
+                             score.player, score.preset, score.topology);
+                    write_response(stream, "201 Created",
+                                   &format!("{}\n", stats::format_time(score.time_ms)))
+                },
+                Err(e) => write_response(stream, "422 Unprocessable Entity", &format!("{}\n", e)),
+            }
+        },
+        (_, "/scores") => write_response(stream, "405 Method Not Allowed", "use GET or POST\n"),
+        _ => write_response(stream, "404 Not Found", "not found\n"),
+    }
+}
+
+/// Serve the leaderboard on `addr` until the process is killed. Connections are handled one at
+/// a time; the server is meant for a team, not the internet. A client that stalls for longer
+/// than `TIMEOUT_SECS` is dropped, so it can't hold up everyone else.
+pub fn serve<A: ToSocketAddrs>(addr: A, leaderboard: &mut Leaderboard) -> io::Result<()> {
+    let listener = try!(TcpListener::bind(addr));
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                let timeout = Some(Duration::from_secs(TIMEOUT_SECS));
+                let result = stream.set_read_timeout(timeout)
+                    .and_then(|_| stream.set_write_timeout(timeout))
+                    .and_then(|_| handle(&mut stream, leaderboard));
+                if let Err(e) = result {
+                    println!("error answering a request: {}", e);
+                }
+            },
+            Err(e) => println!("error accepting a connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Submit a win to the leaderboard at `addr` (`host:port`). Returns the server's answer, which is
+/// the accepted time on success and the reason on refusal. A server that stalls for longer than
+/// `TIMEOUT_SECS` is given up on.
+pub fn submit(addr: &str, player: &str, replay: &Replay) -> Result<String, String> {
+    if player.contains(|c: char| c.is_control()) {
+        return Err("player names can't contain control characters".to_owned());
+    }
+    let body = replay.to_string();
+    let mut stream = try!(connect(addr)
+        .map_err(|e| format!("couldn't reach the leaderboard at {}: {}", addr, e)));
+    try!(write!(stream, "POST /scores HTTP/1.0\r\nHost: {}\r\nX-Player: {}\r\n\
+                         Content-Type: text/plain\r\nContent-Length: {}\r\n\r\n{}",
+                addr, player, body.len(), body)
+        .map_err(|e| format!("couldn't send the replay: {}", e)));
+
+    let mut response = String::new();
+    try!(stream.read_to_string(&mut response)
+        .map_err(|e| format!("couldn't read the answer: {}", e)));
+    let status = response.lines().next().unwrap_or("");
+    let answer = match response.find("\r\n\r\n") {
+        Some(i) => response[i + 4..].trim().to_owned(),
+        None => String::new(),
+    };
+    if status.split(' ').nth(1) == Some("201") {
+        Ok(answer)
+    } else {
+        Err(answer)
+    }
+}
+
+/// Connect to the first address `addr` resolves to that answers, with `TIMEOUT_SECS` on the
+/// connection and on every read and write after it.
+fn connect(addr: &str) -> io::Result<TcpStream> {
+    let timeout = Duration::from_secs(TIMEOUT_SECS);
+    let mut error = io::Error::new(io::ErrorKind::NotFound, "the address resolves to nothing");
+    for addr in try!(addr.to_socket_addrs()) {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                try!(stream.set_read_timeout(Some(timeout)));
+                try!(stream.set_write_timeout(Some(timeout)));
+                return Ok(stream);
+            },
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::Leaderboard;
+    use game::{Game, Preset};
+    use replay::tests::win;
+    use replay::Replay;
+    use topology::Topology;
+
+    /// An empty leaderboard stored in a file of its own.
+    fn leaderboard(name: &str) -> Leaderboard {
+        let path = env::temp_dir().join(format!("minesweeper-leaderboard-{}.tsv", name));
+        let _ = fs::remove_file(&path);
+        Leaderboard::open(&path).unwrap()
+    }
+
+    #[test]
+    fn honest_wins() {
+        let mut board = leaderboard("honest");
+        let replay = win(Preset::Beginner, 3);
+        let score = board.submit(" ada ", &replay).unwrap();
+        assert_eq!(score.player, "ada");
+        assert_eq!(score.time_ms, replay.moves.last().unwrap().time_ms);
+        // The same seed on another board is another game.
+        assert!(board.submit("ada", &win(Preset::Intermediate, 3)).is_ok());
+        assert_eq!(board.top(Some("beginner"), None, 10).len(), 1);
+    }
+
+    #[test]
+    fn refusals() {
+        let mut board = leaderboard("refusals");
+        let replay = win(Preset::Beginner, 4);
+
+        let mut forged = replay.clone();
+        forged.board_hash ^= 1;
+        assert!(board.submit("ada", &forged).is_err());
+
+        let mut hurried = replay.clone();
+        for (i, step) in hurried.moves.iter_mut().enumerate() {
+            step.time_ms = i as u64 * 10;
+        }
+        assert!(board.submit("ada", &hurried).is_err());
+
+        let mut game = Game::new(Preset::Beginner, Topology::default(), 5);
+        let mine = game.map.cells().find(|&(_, cell)| cell.is_mine()).unwrap().0;
+        game.open(mine);
+        assert!(board.submit("ada", &Replay::from_game(&game)).is_err());
+
+        assert!(board.submit("bad\nname", &replay).is_err());
+        assert!(board.submit("ada", &replay).is_ok());
+        assert!(board.submit("bob", &replay).is_err());
+        assert_eq!(board.scores.len(), 1);
+    }
+}
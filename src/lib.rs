@@ -0,0 +1,22 @@
+//! The Minesweeper engine, shared by the game and the leaderboard server.
+
+extern crate rand;
+extern crate ansi_term;
+
+pub mod assets;
+pub mod bindings;
+pub mod board;
+pub mod coord;
+pub mod game;
+pub mod leaderboard;
+pub mod map;
+pub mod minefield;
+pub mod paths;
+pub mod render;
+pub mod replay;
+pub mod settings;
+pub mod skin;
+pub mod solver;
+pub mod stats;
+pub mod topology;
+pub mod world;
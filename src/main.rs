@@ -1,8 +1,8 @@
 
-extern crate rand;
 extern crate ansi_term;
-
-mod map;
+extern crate image;
+extern crate minesweeper;
+extern crate rand;
 
 
 #[macro_use]extern crate conrod;
@@ -10,24 +10,58 @@ extern crate piston;
 extern crate piston_window;
 
 use conrod::{
-    color,
+    Button,
     Canvas,
-    Color,
     Colorable,
+    DropDownList,
     Frameable,
     Labelable,
+    NumberDialer,
     Positionable,
+    Rect,
+    Rectangle,
     Sizeable,
     Text,
-    Theme,
     Toggle,
     Widget,
-    WidgetMatrix,
 };
-use piston_window::{EventLoop, Glyphs, OpenGL, PistonWindow, UpdateEvent, WindowSettings};
+use piston_window::{EventLoop, FocusEvent, Glyphs, Key, OpenGL, PistonWindow, PressEvent,
+                    ReleaseEvent, UpdateEvent, WindowSettings};
+use piston_window::Button as Input;
+use std::cmp;
+use std::env;
+use std::fs::File;
+use std::io::{self, Write};
+use std::panic;
+use std::process;
 use std::sync::mpsc;
+use std::thread;
 
+use minesweeper::{assets, game, leaderboard, replay, stats};
+use minesweeper::bindings::{self, Modifiers};
+use minesweeper::board::Board;
+use minesweeper::coord::Coord;
+use minesweeper::game::Action;
+use minesweeper::settings::{self, Settings};
+use minesweeper::skin;
+use minesweeper::topology::{Neighbourhood, Tiling, Topology};
+
+use board_view::{BoardView, Viewport};
+use layout::WindowLayout;
+use minimap::Minimap;
+use screen::{Screen, Screens};
+use sprites::Sprites;
+use theme::Palette;
+
+mod board_view;
 mod cell;
+mod cli;
+mod layout;
+mod minimap;
+mod screen;
+mod sprites;
+mod theme;
+mod tui;
 
 
 /// Conrod is backend agnostic. Here, we define the `piston_window` backend to use for our `Ui`.
@@ -35,91 +69,406 @@ type Backend = (<piston_window::G2d<'static> as conrod::Graphics>::Texture, Glyp
 type Ui = conrod::Ui<Backend>;
 type UiCell<'a> = conrod::UiCell<'a, Backend>;
 
-enum AppStatus {
-    Playing,
-    Failed,
-    Finished,
-}
+/// Everything the GUI shows, and keeps from one update to the next.
+struct App {
+    game: game::Game,
+    stats: stats::Stats,
+    /// Whether the finished game has been written to `stats` yet.
+    recorded: bool,
+    /// The screen shown, and the ones Esc goes back to.
+    screens: Screens,
+    /// Set when the player asks to quit.
+    quit: bool,
+    /// The board and topology picked in the new game dialog, dealt once the player starts.
+    next_preset: game::Preset,
+    next_topology: Topology,
+    /// The layer of a layered board being shown and played on.
+    layer: usize,
+
+    /// The colour theme, and the settings it and everything else the player can change are
+    /// saved in.
+    palette: Palette,
+    settings: Settings,
+    /// Whether the skin named in the settings is loaded, so the board leaves its cells to it.
+    skinned: bool,
+    /// Where the board was last drawn, for the skin to draw over it.
+    board_rect: Option<Rect>,
+    /// What the player sees of `game`, built once an update for the board's widgets and kept
+    /// for the skin's sprites, so that big boards aren't copied tile by tile more than needed.
+    board: Board,
+    /// A channel for sending clicks from the `BoardView`.
+    elem_sender: mpsc::Sender<board_view::Event>,
+    elem_receiver: mpsc::Receiver<board_view::Event>,
+    /// How the board is zoomed and panned.
+    viewport: Viewport,
+    /// Whether space is held, so that dragging the board pans it.
+    space_held: bool,
+    /// The modifier keys held, which change what the mouse buttons do.
+    modifiers: Modifiers,
+    /// The cell under the mouse, which bound keys act on.
+    hovered: Option<Coord>,
+    /// The cell buttons are held on, and what for, for the skin to draw it and its neighbours
+    /// pressed.
+    held: Option<(Coord, Action)>,
 
-/// This struct holds all of the variables used to demonstrate application data being passed
-/// through the widgets. If some of these seem strange, that's because they are! Most of these
-/// simply represent the aesthetic state of different parts of the GUI to offer visual feedback
-/// during interaction with the widgets.
-struct DemoApp {
-    map: map::Map,
-
-    /// Background color (for demonstration of button and sliders).
-    bg_color: Color,
-    /// Should the button be shown (for demonstration of button).
-    /// and the title.
-    title_pad: f64,
-    /// The height of the vertical sliders (we will play with this
-    /// using a number_dialer).
-    /// The widget frame width (we'll use this to demo Framing
-    /// and number_dialer).
-    frame_width: f64,
-    /// A vector of strings for drop_down_list demonstration.
-    /// A channel for sending results from the `WidgetMatrix`.
-    elem_sender: mpsc::Sender<(usize, usize, cell::Interaction)>,
-    elem_receiver: mpsc::Receiver<(usize, usize, cell::Interaction)>,
-
-    status: AppStatus,
     title: String,
 }
 
-impl DemoApp {
+/// What a button of the main menu or the pause screen does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Choice {
+    /// Go back to the game in progress.
+    Resume,
+    NewGame,
+    Open(Screen),
+    MainMenu,
+    Quit,
+}
 
-    /// Constructor for the Demonstration Application model.
-    fn new() -> DemoApp {
+impl App {
+    /// Open on the main menu, or straight onto `game` if the command line dealt one.
+    fn new(game: Option<game::Game>) -> App {
         let (elem_sender, elem_receiver) = mpsc::channel();
 
-        use map::Map;
+        let stats = stats::Stats::load().unwrap_or_else(|e| {
+            println!("Couldn't read statistics, starting afresh: {}", e);
+            Default::default()
+        });
 
-        let mut map : Map = Default::default();
-        map.populate(10, 10, 20);
-        println!("{}", &map);
+        let settings = Settings::load_or_warn();
+        let palette = Palette::named(&settings.theme).unwrap_or_else(|| {
+            println!("Unknown theme {}, using the classic one", settings.theme);
+            Palette::default()
+        });
 
-        DemoApp {
-            bg_color: color::rgb(0.2, 0.35, 0.45),
-            title_pad: 350.0,
-            frame_width: 1.0,
+        let mut screens = Screens::new();
+        let game = match game {
+            Some(mut game) => {
+                game.first_click = settings.first_click;
+                game.question_marks = settings.question_marks;
+                screens.play();
+                game
+            },
+            None => App::deal(&settings, settings.preset, Topology::default()),
+        };
+
+        App {
+            palette: palette,
+            settings: settings,
+            skinned: false,
+            board_rect: None,
+            board: game.board(),
             elem_sender: elem_sender,
             elem_receiver: elem_receiver,
-            map: map,
+            viewport: Viewport::fit(),
+            space_held: false,
+            modifiers: Modifiers::none(),
+            hovered: None,
+            held: None,
+            stats: stats,
+            recorded: false,
+            screens: screens,
+            quit: false,
+            next_preset: game.preset,
+            next_topology: game.topology,
+            layer: 0,
             title: "Minesweeper".to_owned(),
-            status: AppStatus::Playing,
+            game: game,
+        }
+    }
+
+    /// A new game, played as the settings say.
+    fn deal(settings: &Settings, preset: game::Preset, topology: Topology) -> game::Game {
+        let mut game = game::Game::random(preset, topology);
+        game.first_click = settings.first_click;
+        game.question_marks = settings.question_marks;
+        game
+    }
+
+    /// Throw the current board away and deal a new one of the same preset.
+    fn new_game(&mut self) {
+        self.game = App::deal(&self.settings, self.game.preset, self.game.topology);
+        self.recorded = false;
+        self.layer = 0;
+        self.viewport = Viewport::fit();
+        self.held = None;
+        self.title = "Minesweeper".to_owned();
+    }
+
+    /// Esc goes back a screen, and pauses a game, as does `p`, which also resumes it. On the
+    /// board, zoom with `+` and `-`, fit the board with `0`, pan with the arrow keys, and hold
+    /// space to pan by dragging. Keys with bindings act on the cell under the mouse.
+    fn key(&mut self, key: Key, pressed: bool) {
+        const STEP: f64 = 40.0;
+        match key {
+            Key::Space => self.space_held = pressed,
+            Key::LShift | Key::RShift => self.modifiers.shift = pressed,
+            Key::LCtrl | Key::RCtrl => self.modifiers.ctrl = pressed,
+            Key::LAlt | Key::RAlt => self.modifiers.alt = pressed,
+            _ => (),
+        }
+        if !pressed {
+            return;
+        }
+        match (key, self.screens.current()) {
+            (Key::Escape, Screen::Game) | (Key::P, Screen::Game) => return self.pause(),
+            (Key::P, Screen::Pause) | (Key::Escape, _) => return self.back(),
+            (_, Screen::Game) => (),
+            _ => return,
+        }
+        if let (Some(action), Some(coord)) = (self.settings.bindings.key(&key_name(key)),
+                                              self.hovered) {
+            self.act(coord, action);
+        }
+        self.viewport = match key {
+            Key::Plus | Key::Equals | Key::NumPadPlus => self.viewport.zoom_by(1.25, [0.0, 0.0]),
+            Key::Minus | Key::NumPadMinus => self.viewport.zoom_by(0.8, [0.0, 0.0]),
+            Key::D0 | Key::NumPad0 => Viewport::fit(),
+            Key::Left => self.viewport.pan_by([STEP, 0.0]),
+            Key::Right => self.viewport.pan_by([-STEP, 0.0]),
+            Key::Up => self.viewport.pan_by([0.0, -STEP]),
+            Key::Down => self.viewport.pan_by([0.0, STEP]),
+            _ => self.viewport,
+        };
+    }
+
+    /// Do what a click or a key asked of a cell.
+    fn act(&mut self, coord: Coord, action: Action) {
+        match action {
+            Action::Open => self.game.open(coord),
+            Action::Flag => self.game.toggle_flag(coord),
+            Action::Chord => self.game.chord(coord),
+        }
+    }
+
+    /// Save the settings after a change.
+    fn save_settings(&self) {
+        if let Err(e) = self.settings.save() {
+            println!("Couldn't save settings: {}", e);
+        }
+    }
+
+    /// Cover the board with the pause screen. The clock stops while the board isn't shown.
+    fn pause(&mut self) {
+        if self.screens.current() == Screen::Game {
+            self.screens.open(Screen::Pause);
+        }
+    }
+
+    /// Go back a screen, quitting from the main menu.
+    fn back(&mut self) {
+        if !self.screens.back() {
+            self.quit = true;
         }
     }
 
+    /// Do what a menu button says.
+    fn choose(&mut self, choice: Choice) {
+        match choice {
+            Choice::Resume => self.screens.play(),
+            Choice::NewGame => {
+                self.next_preset = self.settings.preset;
+                self.next_topology = self.game.topology;
+                self.screens.open(Screen::NewGame);
+            },
+            Choice::Open(screen) => self.screens.open(screen),
+            Choice::MainMenu => self.screens.menu(),
+            Choice::Quit => self.quit = true,
+        }
+    }
+
+    /// Pick the difficulty named `name` for the new game. A custom board starts out as one
+    /// layer the size of the board picked before.
+    fn choose_difficulty(&mut self, name: &str) {
+        match game::Preset::standard(name) {
+            Some(preset) => self.next_preset = preset,
+            None => {
+                let (width, height, mines) = self.next_preset.dimensions();
+                self.choose_custom(width, height, 1, mines);
+            },
+        }
+    }
+
+    /// Pick the size of a custom board, keeping at least one cell free of mines.
+    fn choose_custom(&mut self, width: usize, height: usize, depth: usize, mines: usize) {
+        let mines = cmp::max(cmp::min(mines, width * height * depth - 1), 1);
+        self.next_preset = game::Preset::Custom {
+            width: width,
+            height: height,
+            depth: depth,
+            mines: mines,
+        };
+    }
+
+    /// Deal the board picked in the new game dialog and play it. The difficulty is remembered
+    /// for the next time the game is started.
+    fn start_game(&mut self) {
+        self.settings.preset = self.next_preset;
+        self.save_settings();
+        self.game.preset = self.next_preset;
+        self.game.topology = self.next_topology;
+        self.new_game();
+        self.screens.play();
+    }
+
+    fn set_theme(&mut self, name: &str) {
+        if let Some(palette) = Palette::named(name) {
+            self.palette = palette;
+            self.settings.theme = name.to_owned();
+            self.save_settings();
+        }
+    }
+
+    /// Draw cells with the skin named `name`, or `flat`. The main loop loads it.
+    fn set_skin(&mut self, name: &str) {
+        self.settings.skin = name.to_owned();
+        self.save_settings();
+    }
+
+    /// Start the bindings from another preset, keeping any changed in the settings file.
+    fn set_bindings(&mut self, preset: &str) {
+        if self.settings.bindings.set_preset(preset).is_ok() {
+            self.save_settings();
+        }
+    }
+
+    fn set_first_click(&mut self, first_click: game::FirstClick) {
+        self.settings.first_click = first_click;
+        self.game.first_click = first_click;
+        self.save_settings();
+    }
+
+    fn set_question_marks(&mut self, question_marks: bool) {
+        self.settings.question_marks = question_marks;
+        self.game.question_marks = question_marks;
+        self.save_settings();
+    }
+
+    fn set_frame_width(&mut self, frame_width: f64) {
+        self.settings.frame_width = frame_width;
+        self.save_settings();
+    }
+
+    /// Write the game to the statistics once it's over.
+    fn record_if_over(&mut self) {
+        if self.recorded {
+            return;
+        }
+        if let Some(record) = stats::Record::from_game(&self.game) {
+            self.recorded = true;
+            if let Err(e) = self.stats.push(record) {
+                println!("Couldn't save statistics: {}", e);
+            }
+            if self.game.status == game::Status::Finished {
+                self.submit_to_leaderboard();
+            }
+        }
+    }
+
+    /// Send the win to the leaderboard named by `MINESWEEPER_LEADERBOARD` (`host:port`), if any.
+    /// The player's name comes from `MINESWEEPER_PLAYER`, falling back to the login name.
+    fn submit_to_leaderboard(&self) {
+        let addr = match env::var("MINESWEEPER_LEADERBOARD") {
+            Ok(addr) => addr,
+            Err(_) => return,
+        };
+        let player = env::var("MINESWEEPER_PLAYER")
+            .or_else(|_| env::var("USER"))
+            .or_else(|_| env::var("USERNAME"))
+            .unwrap_or_else(|_| "anonymous".to_owned());
+        let replay = replay::Replay::from_game(&self.game);
+
+        // Don't hold the UI up while the server re-plays the game.
+        thread::spawn(move || {
+            match leaderboard::submit(&addr, &player, &replay) {
+                Ok(time) => println!("Leaderboard accepted {} for {}", time, player),
+                Err(e) => println!("Leaderboard refused the win: {}", e),
+            }
+        });
+    }
+
 }
 
 
 fn main() {
-    // Change this to OpenGL::V2_1 if not working.
-    let opengl = OpenGL::V3_2;
+    let args: Vec<String> = env::args().skip(1).collect();
+    let game = match cli::run(&args) {
+        Ok(cli::Next::Gui(game)) => game,
+        Ok(cli::Next::Exit(code)) => process::exit(code),
+        Err(e) => {
+            let _ = writeln!(io::stderr(), "error: {}", e);
+            process::exit(e.code());
+        },
+    };
 
-    // Construct the window.
-    let mut window: PistonWindow =
-        WindowSettings::new("MineSweeper", [1100, 560])
-            .opengl(opengl).exit_on_esc(true).vsync(true).build().unwrap();
+    let mut app = App::new(game);
+
+    // Construct the window, large enough for the whole board, even if a smaller size was saved.
+    let board = app.game.board();
+    let size = match app.settings.window {
+        Some(size) => WindowLayout::clamp([size[0] as f64, size[1] as f64], &board),
+        None => WindowLayout::clamp([1100.0, 560.0], &board),
+    };
+    let size = [size[0] as u32, size[1] as u32];
+    let mut window = match open_window(size) {
+        Ok(window) => window,
+        Err(tried) => {
+            let _ = writeln!(io::stderr(), "Couldn't open a window:\n{}", tried);
+            let _ = writeln!(io::stderr(), "Playing in the terminal instead.");
+            if let Err(e) = tui::run(app.game) {
+                let _ = writeln!(io::stderr(), "error: {}", e);
+                process::exit(cli::FAILURE);
+            }
+            return;
+        },
+    };
 
     // construct our `Ui`.
     let mut ui = {
-        let font_path = "assets/fonts/NotoSans/NotoSans-Regular.ttf";
-        let theme = Theme::default();
-        let glyph_cache = Glyphs::new(&font_path, window.factory.clone());
-        Ui::new(glyph_cache.unwrap(), theme)
+        let theme = app.palette.conrod_theme();
+        Ui::new(load_font(&window), theme)
     };
 
-    // Our dmonstration app that we'll control with our GUI.
-    let mut app = DemoApp::new();
-
     window.set_ups(60);
 
+    // The skin's sheet, loaded again whenever the settings name another.
+    let mut sprites = None;
+    let mut loaded_skin = String::new();
+
     // Poll events from the window.
     while let Some(event) = window.next() {
+        if app.quit {
+            break;
+        }
         ui.handle_event(&event);
 
+        // Widgets without colours of their own follow the theme.
+        if ui.theme.name != app.palette.name {
+            ui.theme = app.palette.conrod_theme();
+        }
+
+        if loaded_skin != app.settings.skin {
+            loaded_skin = app.settings.skin.clone();
+            sprites = if loaded_skin == sprites::FLAT {
+                None
+            } else {
+                Sprites::load(&mut window, &loaded_skin)
+            };
+            app.skinned = sprites.is_some();
+        }
+
+        // Don't let the clock run while the player is in another window.
+        if let Some(false) = event.focus_args() {
+            app.pause();
+        }
+
+        if let Some(Input::Keyboard(key)) = event.press_args() {
+            app.key(key, true);
+        }
+        if let Some(Input::Keyboard(key)) = event.release_args() {
+            app.key(key, false);
+        }
+
         // We'll set all our widgets in a single function called `set_widgets`.
         // At the moment conrod requires that we set our widgets in the Render loop,
         // however soon we'll add support so that you can set your Widgets at any arbitrary
@@ -128,13 +477,98 @@ fn main() {
 
         // Draw our Ui!
         //
-        // The `draw_if_changed` method only re-draws the GUI if some `Widget`'s `Element`
-        // representation has changed. Normally, a `Widget`'s `Element` should only change
-        // if a Widget was interacted with in some way, however this is up to the `Widget`
-        // designer's discretion.
-        //
-        // If instead you need to re-draw your conrod GUI every frame, use `Ui::draw`.
-        window.draw_2d(&event, |c, g| ui.draw_if_changed(c, g));
+        // The whole `Ui` is drawn every frame rather than with `draw_if_changed`, as the skin's
+        // sprites are drawn over it and would otherwise be left behind by a redraw.
+        window.draw_2d(&event, |c, g| {
+            ui.draw(c, g);
+            if let (Some(ref sprites), Some(rect)) = (sprites.as_ref(), app.board_rect) {
+                let pressed = board_view::pressed_cells(&app.board, app.held);
+                // A lost game shows the mine that went off and the wrong flags.
+                let lost = if app.game.status == game::Status::Failed {
+                    Some(&app.game.map)
+                } else {
+                    None
+                };
+                sprites.draw(c, g, [ui.win_w, ui.win_h], &app.board, app.layer, rect,
+                             app.viewport, &pressed, lost);
+            }
+        });
+    }
+
+    // Open at the same size next time, or at the least the board needs.
+    let size = WindowLayout::clamp([ui.win_w, ui.win_h], &app.game.board());
+    app.settings.window = Some([size[0] as u32, size[1] as u32]);
+    app.save_settings();
+}
+
+/// The OpenGL versions a window is asked for, newest first. Graphics drivers that can't give
+/// one often can give an older one, and piston draws with any of them.
+const OPENGL: [OpenGL; 4] = [OpenGL::V3_2, OpenGL::V3_1, OpenGL::V3_0, OpenGL::V2_1];
+
+/// A window of `size` pixels, with the first OpenGL version in `OPENGL` that works. Without
+/// one, the error lists each version tried and why it failed, a line each. Call it only before
+/// any other thread is started: see below.
+fn open_window(size: [u32; 2]) -> Result<PistonWindow, String> {
+    let mut tried = vec![];
+    for &opengl in OPENGL.iter() {
+        let (major, minor) = opengl.get_major_minor();
+        // Some backends panic rather than fail when there's no display at all, so a panic is
+        // taken as one more failure, its message kept as the reason. The hook silenced meanwhile
+        // is the whole process's, and would hide a panic on any other thread too, which is why
+        // the window is opened before the game starts any.
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| ()));
+        let built = panic::catch_unwind(|| {
+            WindowSettings::new("MineSweeper", size).opengl(opengl).vsync(true).build()
+        });
+        panic::set_hook(hook);
+        let built = built.unwrap_or_else(|payload| {
+            Err(payload.downcast_ref::<String>().cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| "the window backend panicked".to_owned()))
+        });
+        match built {
+            Ok(window) => {
+                if !tried.is_empty() {
+                    let _ = writeln!(io::stderr(), "OpenGL versions that didn't work:\n{}",
+                                     tried.join("\n"));
+                    let _ = writeln!(io::stderr(), "Using OpenGL {}.{}", major, minor);
+                }
+                return Ok(window);
+            },
+            Err(e) => tried.push(format!("  OpenGL {}.{}: {}", major, minor, e)),
+        }
+    }
+    Err(tried.join("\n"))
+}
+
+/// The font in the assets directory if there is one, or else the built-in Noto Sans. A font that
+/// can't be read is reported, and the built-in one used instead.
+fn load_font(window: &PistonWindow) -> Glyphs {
+    if let Some(path) = assets::find(assets::FONT_PATH) {
+        // Opening the file first gives a plainer message for the likeliest failures.
+        let glyphs = File::open(&path).map_err(|e| e.to_string()).and_then(|_| {
+            Glyphs::new(&path, window.factory.clone()).map_err(|e| format!("{:?}", e))
+        });
+        match glyphs {
+            Ok(glyphs) => return glyphs,
+            Err(e) => {
+                let _ = writeln!(io::stderr(), "Couldn't load {}, using the built-in font: {}",
+                                 path.display(), e);
+            },
+        }
+    }
+    Glyphs::from_bytes(assets::FONT, window.factory.clone()).expect("the built-in font is broken")
+}
+
+/// The name a key has in the bindings, such as `z`, `1` or `space`.
+fn key_name(key: Key) -> String {
+    let name = format!("{:?}", key).to_lowercase();
+    // The digits above the letters are called `D0` to `D9`.
+    if name.len() == 2 && name.starts_with('d') && name[1..].chars().all(|c| c.is_digit(10)) {
+        name[1..].to_owned()
+    } else {
+        name
     }
 }
 
@@ -144,105 +578,491 @@ fn main() {
 /// the `Ui` at their given indices. Every other time this get called, the `Widget`s will avoid any
 /// allocations by updating the pre-existing cached state. A new graphical `Element` is only
 /// retrieved from a `Widget` in the case that it's `State` has changed in some way.
-fn set_widgets(ui: &mut UiCell, app: &mut DemoApp) {
+fn set_widgets(ui: &mut UiCell, app: &mut App) {
+    let screen = app.screens.current();
+
+    // The clock only runs while the board is shown, so it can't be studied on the clock's time.
+    if screen == Screen::Game {
+        app.game.resume();
+    } else {
+        app.game.pause();
+    }
 
     // We can use this `Canvas` as a parent Widget upon which we can place other widgets.
     Canvas::new()
-        .frame(app.frame_width)
-        .pad(30.0)
-        .color(app.bg_color)
-        .scroll_kids()
+        .frame(app.settings.frame_width)
+        .pad(layout::PAD)
+        .color(app.palette.background)
         .set(CANVAS, ui);
 
-    // Text example.
-    Text::new(app.title.as_str())
-        .top_left_with_margins_on(CANVAS, 0.0, app.title_pad)
+    let time = stats::format_time(game::millis(app.game.elapsed()));
+    let title = match screen {
+        Screen::Game => format!("{}  {}", app.title, time),
+        Screen::Pause => format!("{}  {}", screen.title(), time),
+        _ => screen.title().to_owned(),
+    };
+    Text::new(&title)
+        .mid_top_of(CANVAS)
         .font_size(32)
-        .color(app.bg_color.plain_contrast())
+        .color(app.palette.background.plain_contrast())
         .set(TITLE, ui);
 
-    // A demonstration using widget_matrix to easily draw
-    // a matrix of any kind of widget.
-    WidgetMatrix::new(app.map.width, app.map.height)
-        .down(20.0)
-        .w_h(260.0, 260.0) // matrix width and height.
-        .each_widget(|_n, col: usize, row: usize| { // called for every matrix elem.
-
-            // Color effect for fun.
-            // let (r, g, b, a) = (
-            //     0.5 + (col as f32 / cols as f32) / 2.0,
-            //     0.75,
-            //     1.0 - (row as f32 / rows as f32) / 2.0,
-            //     1.0
-            // );
-
-            // Now return the widget we want to set in each element position.
-            // You can return any type that implements `Widget`.
-            // The returned widget will automatically be positioned and sized to the matrix
-            // element's rectangle.
-            let elem = app.map.grid[row * app.map.width + col].mine;
-            let elem_sender = app.elem_sender.clone();
-
-            let ref cell_data = app.map.grid[row * app.map.width + col];
-            let label = match cell_data.interaction {
-                map::Interaction::Opened => match cell_data.nearby {
-                    0 => " ",
-                    1 => "1",
-                    2 => "2",
-                    3 => "3",
-                    4 => "4",
-                    5 => "5",
-                    6 => "6",
-                    7 => "7",
-                    8 => "8",
-                    _ => "#",
-                },
-                map::Interaction::Undiscovered => " ",
-                map::Interaction::Flagged => "!",
-            };
+    if screen != Screen::Game {
+        app.board_rect = None;
+        app.hovered = None;
+        app.held = None;
+    }
+    match screen {
+        Screen::Menu => {
+            let mut choices = vec![];
+            if !app.game.moves.is_empty() && !app.game.is_over() {
+                choices.push(("Continue", Choice::Resume));
+            }
+            choices.push(("New game", Choice::NewGame));
+            choices.push(("Statistics", Choice::Open(Screen::Stats)));
+            choices.push(("Settings", Choice::Open(Screen::Settings)));
+            choices.push(("About", Choice::Open(Screen::About)));
+            choices.push(("Quit", Choice::Quit));
+            set_menu_widgets(ui, app, &choices);
+        },
+        Screen::Pause => {
+            set_menu_widgets(ui, app, &[("Resume", Choice::Resume),
+                                        ("New game", Choice::NewGame),
+                                        ("Settings", Choice::Open(Screen::Settings)),
+                                        ("Main menu", Choice::MainMenu)]);
+        },
+        Screen::NewGame => set_new_game_widgets(ui, app),
+        Screen::Game => {
+            set_sidebar_widgets(ui, app);
+            set_board_widgets(ui, app);
+        },
+        Screen::Stats => {
+            set_back_widget(ui, app);
+            set_stats_widgets(ui, app);
+        },
+        Screen::Settings => {
+            set_back_widget(ui, app);
+            set_settings_widgets(ui, app);
+        },
+        Screen::About => {
+            set_back_widget(ui, app);
+            set_about_widgets(ui, app);
+        },
+    }
+}
 
-            let (r, g, b) = match cell_data.interaction {
-                map::Interaction::Opened => (0.8, 0.8, 0.8),
-                map::Interaction::Undiscovered => (0.5, 0.5, 0.5),
-                map::Interaction::Flagged => (0.5, 0.5, 0.5),
-            };
-            let cell = cell::Cell::new()
-                .w_h(200.0, 50.0)
-                .down_from(TITLE, 45.0)
-                .rgb(r, g, b)
-                .label(label)
-                .react(move |btn: cell::Interaction| elem_sender.send((col, row, btn)).unwrap());
-            cell
-        })
-        .set(TOGGLE_MATRIX, ui);
-
-    // Receive updates to the matrix from the `WidgetMatrix`.
-    while let Ok((col, row, btn)) = app.elem_receiver.try_recv() {
-        match btn {
-            cell::Interaction::LeftClicked => {
-                if app.map.grid[row * app.map.width + col].mine {
-                    app.title = "EXPLODE!".to_owned();
-                    app.status = AppStatus::Failed;
-                } else {
-                    app.map.reveal(col, row);
-                }
-            },
-            cell::Interaction::RightClicked => {
-                match app.map.grid[row * app.map.width + col].interaction {
-                    map::Interaction::Flagged => app.map.grid[row * app.map.width + col].interaction = map::Interaction::Undiscovered,
-                    map::Interaction::Undiscovered => app.map.grid[row * app.map.width + col].interaction = map::Interaction::Flagged,
-                    _ => (),
-                };
-                println!("Right Clicked");
+/// A column of buttons down the middle of the window.
+fn set_menu_widgets(ui: &mut UiCell, app: &mut App, choices: &[(&str, Choice)]) {
+    for (i, &(label, choice)) in choices.iter().enumerate() {
+        let button = Button::new()
+            .w_h(layout::SIDEBAR_WIDTH, 40.0)
+            .color(app.palette.button)
+            .frame(app.settings.frame_width)
+            .label(label)
+            .react(|| app.choose(choice));
+        if i == 0 {
+            button.mid_top_with_margin_on(CANVAS, layout::HEADER_HEIGHT).set(MENU_BUTTON, ui);
+        } else {
+            button.down_from(MENU_BUTTON + (i - 1), 10.0).set(MENU_BUTTON + i, ui);
+        }
+    }
+}
+
+/// The button taking a screen back to the one it was opened from, where the sidebar would be.
+fn set_back_widget(ui: &mut UiCell, app: &mut App) {
+    Button::new()
+        .w_h(layout::SIDEBAR_WIDTH, 30.0)
+        .top_left_of(CANVAS)
+        .color(app.palette.button)
+        .frame(app.settings.frame_width)
+        .label("Back")
+        .react(|| app.back())
+        .set(BACK, ui);
+}
+
+/// The buttons beside the board.
+fn set_sidebar_widgets(ui: &mut UiCell, app: &mut App) {
+    Button::new()
+        .w_h(layout::SIDEBAR_WIDTH, 30.0)
+        .top_left_of(CANVAS)
+        .color(app.palette.button)
+        .frame(app.settings.frame_width)
+        .label("Pause")
+        .react(|| app.pause())
+        .set(PAUSE, ui);
+
+    Button::new()
+        .w_h(layout::SIDEBAR_WIDTH, 30.0)
+        .down_from(PAUSE, 10.0)
+        .color(app.palette.button)
+        .frame(app.settings.frame_width)
+        .label("New game")
+        .react(|| app.new_game())
+        .set(NEW_GAME, ui);
+
+    Button::new()
+        .w_h(layout::SIDEBAR_WIDTH, 30.0)
+        .down_from(NEW_GAME, 10.0)
+        .color(app.palette.button)
+        .frame(app.settings.frame_width)
+        .label("Fit board")
+        .react(|| app.viewport = Viewport::fit())
+        .set(FIT, ui);
+
+    // Layered boards are shown one slice at a time.
+    if app.game.preset.depth() > 1 {
+        Button::new()
+            .w_h(layout::SIDEBAR_WIDTH, 30.0)
+            .down_from(FIT, 30.0)
+            .color(app.palette.button)
+            .frame(app.settings.frame_width)
+            .label("Layer up")
+            .react(|| if app.layer > 0 { app.layer -= 1 })
+            .set(LAYER_UP, ui);
+
+        Text::new(&format!("Layer {}/{}", app.layer + 1, app.game.preset.depth()))
+            .down_from(LAYER_UP, 10.0)
+            .font_size(16)
+            .color(app.palette.background.plain_contrast())
+            .set(LAYER_TEXT, ui);
+
+        Button::new()
+            .w_h(layout::SIDEBAR_WIDTH, 30.0)
+            .down_from(LAYER_TEXT, 10.0)
+            .color(app.palette.button)
+            .frame(app.settings.frame_width)
+            .label("Layer down")
+            .react(|| if app.layer + 1 < app.game.preset.depth() { app.layer += 1 })
+            .set(LAYER_DOWN, ui);
+    }
+}
+
+/// The board itself, and handling of the clicks made on it.
+fn set_board_widgets(ui: &mut UiCell, app: &mut App) {
+    let board = app.game.board();
+    let window_layout = WindowLayout::new([ui.win_w, ui.win_h], &board);
+
+    // One widget draws the cells on screen and reports clicks, zooming and panning.
+    let elem_sender = app.elem_sender.clone();
+    BoardView::new(&board, app.layer)
+        .top_left_with_margins_on(CANVAS, window_layout.board_offset[1],
+                                  window_layout.board_offset[0])
+        .w_h(window_layout.board[0], window_layout.board[1])
+        .crop_kids()
+        .palette(&app.palette)
+        .viewport(app.viewport)
+        .drag_pans(app.space_held)
+        .bindings(&app.settings.bindings, app.modifiers)
+        .skinned(app.skinned)
+        .react(move |event| elem_sender.send(event).unwrap())
+        .set(BOARD, ui);
+    app.board_rect = ui.rect_of(BOARD);
+
+    // Under the buttons, an overview of the board while only part of it is shown.
+    let board_size = window_layout.board;
+    let window = app.viewport.window(&board, board_size);
+    if window[0] > 0.0 || window[1] > 0.0 || window[2] < 1.0 || window[3] < 1.0 {
+        let viewport = &mut app.viewport;
+        Minimap::new(&board, app.layer)
+            .bottom_left_of(CANVAS)
+            .w_h(layout::SIDEBAR_WIDTH, layout::SIDEBAR_WIDTH)
+            .window(window)
+            .react(|fraction| *viewport = viewport.centred_on(&board, board_size, fraction))
+            .set(MINIMAP, ui);
+    }
+
+    // Receive what was done on the board.
+    let mut clicked = false;
+    while let Ok(event) = app.elem_receiver.try_recv() {
+        match event {
+            board_view::Event::Click(coord, action) => {
+                app.act(coord, action);
+                clicked = true;
             },
-            cell::Interaction::BothClicked => {
-                println!("Both Clicked");
-            }
-            _ => (),
+            board_view::Event::View(viewport) => app.viewport = viewport,
+            board_view::Event::Held(held) => app.held = held,
+            board_view::Event::Hover(hovered) => app.hovered = hovered,
         }
+    }
+
+    match app.game.status {
+        game::Status::Playing => (),
+        game::Status::Failed => app.title = "EXPLODE!".to_owned(),
+        game::Status::Finished => app.title = "Cleared!".to_owned(),
+    }
+    app.record_if_over();
+    // The sprites are drawn from the same tiles, unless a click has just changed them.
+    app.board = if clicked { app.game.board() } else { board };
+}
+
+/// Win rate, streaks, best times and a chart of winning times for the current preset.
+fn set_stats_widgets(ui: &mut UiCell, app: &mut App) {
+    let preset = app.game.preset.name();
+    let summary = app.stats.summary(Some(preset));
+    let text_color = app.palette.background.plain_contrast();
 
+    let mut lines = format!("Statistics: {}\n\n", preset);
+    lines.push_str(&stats::totals_text(&summary));
+    lines.push_str("\n");
+    // The best times of every category, not just the current preset's.
+    lines.push_str(&stats::best_times_text(&app.stats.summary(None)));
+    Text::new(&lines)
+        .top_left_with_margins_on(CANVAS, layout::HEADER_HEIGHT,
+                                  layout::SIDEBAR_WIDTH + layout::GAP)
+        .font_size(16)
+        .color(text_color)
+        .set(STATS_TEXT, ui);
+
+    // One bar per bucket, scaled so the tallest is `CHART_HEIGHT` high.
+    const CHART_HEIGHT: f64 = 150.0;
+    const BAR_WIDTH: f64 = 24.0;
+    let tallest = summary.distribution.iter().cloned().max().unwrap_or(0).max(1);
+    for (i, &count) in summary.distribution.iter().enumerate() {
+        let height = CHART_HEIGHT * count as f64 / tallest as f64;
+        Rectangle::fill([BAR_WIDTH, height.max(1.0)])
+            .bottom_left_with_margins_on(CANVAS, 40.0, layout::SIDEBAR_WIDTH + layout::GAP +
+                                                      i as f64 * (BAR_WIDTH + 6.0))
+            .color(text_color)
+            .set(STATS_BAR + i, ui);
+        Text::new(&stats::format_time(i as u64 * summary.bucket_ms))
+            .mid_bottom_with_margin_on(CANVAS, 0.0)
+            .x_relative_to(STATS_BAR + i, 0.0)
+            .font_size(10)
+            .color(text_color)
+            .set(STATS_LABEL + i, ui);
     }
+}
+
+/// Everything kept in the settings file, each change saved as it is made.
+fn set_settings_widgets(ui: &mut UiCell, app: &mut App) {
+    const LABEL_WIDTH: f64 = 140.0;
+    const FIELD_WIDTH: f64 = 200.0;
+    let text_color = app.palette.background.plain_contrast();
+    let frame_width = app.settings.frame_width;
+    let button_color = app.palette.button;
+
+    // The labels are numbered down the left of the fields, whichever fields are shown.
+    let mut label = 0;
+    let mut set_label = |ui: &mut UiCell, text: &str, field| {
+        Text::new(text)
+            .left_from(field, 10.0)
+            .align_middle_y_of(field)
+            .w(LABEL_WIDTH)
+            .font_size(16)
+            .color(text_color)
+            .set(SETTINGS_LABEL + label, ui);
+        label += 1;
+    };
+
+    // Drop-down lists hold their own copy of the choices, and give back the index picked.
+    let policies = [game::FirstClick::Any, game::FirstClick::Safe, game::FirstClick::Opening];
+    let mut names: Vec<String> = policies.iter().map(|policy| policy.name().to_owned()).collect();
+    let mut selected = policies.iter().position(|&policy| policy == app.settings.first_click);
+    DropDownList::new(&mut names, &mut selected)
+        .w_h(FIELD_WIDTH, 30.0)
+        .top_left_with_margins_on(CANVAS, layout::HEADER_HEIGHT,
+                                  layout::SIDEBAR_WIDTH + layout::GAP + LABEL_WIDTH)
+        .color(button_color)
+        .frame(frame_width)
+        .react(|_: &mut Option<usize>, i: usize, _: &str| app.set_first_click(policies[i]))
+        .set(FIRST_CLICK, ui);
+    set_label(ui, "First click", FIRST_CLICK);
+
+    let question_marks = app.settings.question_marks;
+    Toggle::new(question_marks)
+        .w_h(FIELD_WIDTH, 30.0)
+        .down_from(FIRST_CLICK, 10.0)
+        .color(button_color)
+        .frame(frame_width)
+        .label(if question_marks { "On" } else { "Off" })
+        .react(|value: bool| app.set_question_marks(value))
+        .set(QUESTION_MARKS, ui);
+    set_label(ui, "Question marks", QUESTION_MARKS);
+
+    let mut themes: Vec<String> = theme::NAMES.iter().map(|&name| name.to_owned()).collect();
+    let mut selected = themes.iter().position(|name| *name == app.settings.theme);
+    DropDownList::new(&mut themes, &mut selected)
+        .w_h(FIELD_WIDTH, 30.0)
+        .down_from(QUESTION_MARKS, 10.0)
+        .color(button_color)
+        .frame(frame_width)
+        .react(|_: &mut Option<usize>, _, name: &str| app.set_theme(name))
+        .set(THEME, ui);
+    set_label(ui, "Theme", THEME);
+
+    let mut skins = skin::available();
+    skins.push(sprites::FLAT.to_owned());
+    let mut selected = skins.iter().position(|name| *name == app.settings.skin);
+    DropDownList::new(&mut skins, &mut selected)
+        .w_h(FIELD_WIDTH, 30.0)
+        .down_from(THEME, 10.0)
+        .color(button_color)
+        .frame(frame_width)
+        .react(|_: &mut Option<usize>, _, name: &str| app.set_skin(name))
+        .set(SKIN, ui);
+    set_label(ui, "Skin", SKIN);
+
+    let mut presets: Vec<String> = bindings::PRESETS.iter().map(|&name| name.to_owned()).collect();
+    let mut selected = presets.iter().position(|name| *name == app.settings.bindings.preset);
+    DropDownList::new(&mut presets, &mut selected)
+        .w_h(FIELD_WIDTH, 30.0)
+        .down_from(SKIN, 10.0)
+        .color(button_color)
+        .frame(frame_width)
+        .react(|_: &mut Option<usize>, _, name: &str| app.set_bindings(name))
+        .set(BINDINGS, ui);
+    set_label(ui, "Bindings", BINDINGS);
+
+    NumberDialer::new(frame_width, 0.0, settings::MAX_FRAME_WIDTH, 1)
+        .w_h(FIELD_WIDTH, 30.0)
+        .down_from(BINDINGS, 10.0)
+        .color(button_color)
+        .frame(frame_width)
+        .react(|value: f64| app.set_frame_width(value))
+        .set(FRAME_WIDTH, ui);
+    set_label(ui, "Frame width", FRAME_WIDTH);
+}
+
+/// The difficulty and topology of a new game, and the size of a custom board.
+fn set_new_game_widgets(ui: &mut UiCell, app: &mut App) {
+    const LABEL_WIDTH: f64 = 140.0;
+    const FIELD_WIDTH: f64 = 200.0;
+    let text_color = app.palette.background.plain_contrast();
+    let frame_width = app.settings.frame_width;
+    let button_color = app.palette.button;
+
+    let mut label = 0;
+    let mut set_label = |ui: &mut UiCell, text: &str, field| {
+        Text::new(text)
+            .left_from(field, 10.0)
+            .align_middle_y_of(field)
+            .w(LABEL_WIDTH)
+            .font_size(16)
+            .color(text_color)
+            .set(NEW_GAME_LABEL + label, ui);
+        label += 1;
+    };
+
+    let mut difficulties: Vec<String> = ["beginner", "intermediate", "expert", "cube", "custom"]
+        .iter().map(|&name| name.to_owned()).collect();
+    let mut selected = difficulties.iter()
+        .position(|name| name == app.next_preset.standard_name());
+    DropDownList::new(&mut difficulties, &mut selected)
+        .w_h(FIELD_WIDTH, 30.0)
+        .top_left_with_margins_on(CANVAS, layout::HEADER_HEIGHT,
+                                  layout::SIDEBAR_WIDTH + layout::GAP + LABEL_WIDTH)
+        .color(button_color)
+        .frame(frame_width)
+        .react(|_: &mut Option<usize>, _, name: &str| app.choose_difficulty(name))
+        .set(DIFFICULTY, ui);
+    set_label(ui, "Difficulty", DIFFICULTY);
+
+    let mut last = DIFFICULTY;
+    if let game::Preset::Custom { width, height, depth, mines } = app.next_preset {
+        if app.next_preset.standard_name() == "custom" {
+            // Either side may grow as far as the other leaves room for under `MAX_CELLS`.
+            let widest = (game::MAX_CELLS / (height * depth)) as f32;
+            let tallest = (game::MAX_CELLS / (width * depth)) as f32;
+            NumberDialer::new(width as f32, 2.0, widest, 0)
+                .w_h(FIELD_WIDTH, 30.0)
+                .down_from(last, 10.0)
+                .color(button_color)
+                .frame(frame_width)
+                .react(|value: f32| app.choose_custom(value as usize, height, depth, mines))
+                .set(CUSTOM_WIDTH, ui);
+            set_label(ui, "Width", CUSTOM_WIDTH);
+
+            NumberDialer::new(height as f32, 2.0, tallest, 0)
+                .w_h(FIELD_WIDTH, 30.0)
+                .down_from(CUSTOM_WIDTH, 10.0)
+                .color(button_color)
+                .frame(frame_width)
+                .react(|value: f32| app.choose_custom(width, value as usize, depth, mines))
+                .set(CUSTOM_HEIGHT, ui);
+            set_label(ui, "Height", CUSTOM_HEIGHT);
+
+            let most = (width * height * depth - 1) as f32;
+            NumberDialer::new(mines as f32, 1.0, most, 0)
+                .w_h(FIELD_WIDTH, 30.0)
+                .down_from(CUSTOM_HEIGHT, 10.0)
+                .color(button_color)
+                .frame(frame_width)
+                .react(|value: f32| app.choose_custom(width, height, depth, value as usize))
+                .set(CUSTOM_MINES, ui);
+            set_label(ui, "Mines", CUSTOM_MINES);
+            last = CUSTOM_MINES;
+        }
+    }
+
+    // Only the topologies that fit the board are offered, so a new size can drop the one picked.
+    let (width, height, _) = app.next_preset.dimensions();
+    let choices = topologies(width, height);
+    if !choices.contains(&app.next_topology) {
+        app.next_topology = Topology::default();
+    }
+    let mut names: Vec<String> = choices.iter().map(|topology| topology.name()).collect();
+    let mut selected = choices.iter().position(|&topology| topology == app.next_topology);
+    DropDownList::new(&mut names, &mut selected)
+        .w_h(FIELD_WIDTH, 30.0)
+        .down_from(last, 10.0)
+        .color(button_color)
+        .frame(frame_width)
+        .react(|_: &mut Option<usize>, i: usize, _: &str| app.next_topology = choices[i])
+        .set(TOPOLOGY, ui);
+    set_label(ui, "Topology", TOPOLOGY);
+
+    Button::new()
+        .w_h(FIELD_WIDTH, 30.0)
+        .down_from(TOPOLOGY, 30.0)
+        .color(button_color)
+        .frame(frame_width)
+        .label("Start")
+        .react(|| app.start_game())
+        .set(START, ui);
+
+    Button::new()
+        .w_h(FIELD_WIDTH, 30.0)
+        .down_from(START, 10.0)
+        .color(button_color)
+        .frame(frame_width)
+        .label("Cancel")
+        .react(|| app.back())
+        .set(CANCEL, ui);
+}
+
+/// The topologies offered for a board of `width` by `height`: square, hex, triangle and knight
+/// grids, then the same as tori, leaving out those that don't fit.
+fn topologies(width: usize, height: usize) -> Vec<Topology> {
+    let mut topologies = vec![];
+    for &wrap in [false, true].iter() {
+        topologies.push(Topology::new(Tiling::Square, wrap));
+        topologies.push(Topology::new(Tiling::Hex, wrap));
+        topologies.push(Topology::new(Tiling::Triangle, wrap));
+        topologies.push(Topology::with_neighbourhood(Tiling::Square, Neighbourhood::knight(),
+                                                     wrap));
+    }
+    topologies.retain(|topology| topology.check(width, height).is_ok());
+    topologies
+}
 
+/// What the game is, and how to play it.
+fn set_about_widgets(ui: &mut UiCell, app: &mut App) {
+    let text = format!("Minesweeper {}\n\
+                        by {}\n\n\
+                        Open every cell without a mine. A number tells how many of the cells \
+                        around it hide one.\n\n\
+                        Esc goes back, and pauses a game. P pauses and resumes.\n\
+                        + and - zoom, 0 fits the board and the arrow keys pan it; hold space \
+                        to pan by dragging.\n\
+                        The mouse buttons and keys are changed under Settings.",
+                       env!("CARGO_PKG_VERSION"), env!("CARGO_PKG_AUTHORS"));
+    Text::new(&text)
+        .top_left_with_margins_on(CANVAS, layout::HEADER_HEIGHT,
+                                  layout::SIDEBAR_WIDTH + layout::GAP)
+        .w(480.0)
+        .font_size(16)
+        .color(app.palette.background.plain_contrast())
+        .set(ABOUT_TEXT, ui);
 }
 
 
@@ -251,11 +1071,38 @@ fn set_widgets(ui: &mut UiCell, app: &mut DemoApp) {
 // To make this easier, conrod provides the `widget_ids` macro, which generates a unique `WidgetId`
 // for each identifier given in the list.
 // The `with n` syntax reserves `n` number of WidgetIds for that identifier, rather than just one.
-// This is often useful when you need to use an identifier in some kind of loop (i.e. like within
-// the use of `WidgetMatrix` as above).
+// This is often useful when you need to use an identifier in some kind of loop (i.e. like the
+// bars of the statistics chart above).
 widget_ids! {
     CANVAS,
     TITLE,
+    BOARD,
+    MINIMAP,
+    MENU_BUTTON with 6,
+    BACK,
+    PAUSE,
+    NEW_GAME,
+    FIT,
+    LAYER_UP,
+    LAYER_TEXT,
+    LAYER_DOWN,
+    DIFFICULTY,
+    CUSTOM_WIDTH,
+    CUSTOM_HEIGHT,
+    CUSTOM_MINES,
+    TOPOLOGY,
+    START,
+    CANCEL,
+    NEW_GAME_LABEL with 5,
+    FIRST_CLICK,
+    QUESTION_MARKS,
+    THEME,
+    SKIN,
+    BINDINGS,
     FRAME_WIDTH,
-    TOGGLE_MATRIX,
+    SETTINGS_LABEL with 6,
+    STATS_TEXT,
+    STATS_BAR with 10,
+    STATS_LABEL with 10,
+    ABOUT_TEXT,
 }
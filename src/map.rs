@@ -2,35 +2,84 @@
 extern crate rand;
 
 use std::fmt;
-use ansi_term::Colour::Red;
+use std::iter;
+use std::slice;
 
-#[derive(Debug)]
+use board::{Board, Solution, Tile};
+pub use coord::{Coord, Coords, Size};
+pub use topology::{Direction, Neighbours, Stencil, Topology};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Interaction {
     Opened,    // discovered
     Undiscovered,
     Flagged,    //
+    /// Marked with a question mark: unsure, but opened like an undiscovered cell.
+    Questioned,
 }
 
-#[derive(Debug)]
-pub struct Cell {
-    pub mine: bool,
-    pub interaction: Interaction,
-    pub nearby: usize,
+/// One cell packed into two bytes: the number of mines nearby in the low byte, then a bit for
+/// the mine and three for the interaction.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Cell(u16);
+
+const NEARBY: u16 = 0xff;
+const MINE: u16 = 1 << 8;
+const OPENED: u16 = 1 << 9;
+const FLAGGED: u16 = 1 << 10;
+const QUESTIONED: u16 = 1 << 11;
+
+impl Cell {
+    pub fn is_mine(&self) -> bool {
+        self.0 & MINE != 0
+    }
+
+    pub fn interaction(&self) -> Interaction {
+        if self.0 & OPENED != 0 {
+            Interaction::Opened
+        } else if self.0 & FLAGGED != 0 {
+            Interaction::Flagged
+        } else if self.0 & QUESTIONED != 0 {
+            Interaction::Questioned
+        } else {
+            Interaction::Undiscovered
+        }
+    }
+
+    /// How many neighbours hold a mine.
+    pub fn nearby(&self) -> usize {
+        (self.0 & NEARBY) as usize
+    }
+
+    /// Whether the cell is still closed and unflagged, so that opening it opens it.
+    pub fn is_closed(&self) -> bool {
+        match self.interaction() {
+            Interaction::Undiscovered | Interaction::Questioned => true,
+            Interaction::Opened | Interaction::Flagged => false,
+        }
+    }
+
+    fn set_interaction(&mut self, interaction: Interaction) {
+        self.0 = self.0 & !(OPENED | FLAGGED | QUESTIONED) | match interaction {
+            Interaction::Opened => OPENED,
+            Interaction::Undiscovered => 0,
+            Interaction::Flagged => FLAGGED,
+            Interaction::Questioned => QUESTIONED,
+        };
+    }
 }
 
+/// The cell as the player sees it: `#` until it's opened, `F` when flagged.
 impl fmt::Display for Cell {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.interaction {
-            Interaction::Undiscovered => {
-                if self.mine {
-                    write!(f, "{}", Red.paint("X"))
-                } else {
-                    write!(f, "{}", self.nearby)
-                }
-            },
-            Interaction::Opened       => write!(f, " "),
-            Interaction::Flagged      => write!(f, "!"),
-        }
+        let tile = match self.interaction() {
+            Interaction::Undiscovered => Tile::Unknown,
+            Interaction::Flagged => Tile::Flagged,
+            Interaction::Questioned => Tile::Questioned,
+            Interaction::Opened if self.is_mine() => Tile::Mine,
+            Interaction::Opened => Tile::Number(self.nearby()),
+        };
+        write!(f, "{}", tile.symbol())
     }
 }
 
@@ -38,155 +87,362 @@ impl fmt::Display for Cell {
 pub struct Map {
     pub width: usize,
     pub height: usize,
+    /// Number of layers. Ordinary boards have one; cells are indexed layer by layer, row by row.
+    pub depth: usize,
     mines: usize,
-    pub grid: Vec<Cell>,
-}
-
-pub enum Direction {
-    Up,
-    UpRight,
-    Right,
-    DownRight,
-    Down,
-    DownLeft,
-    Left,
-    UpLeft,
+    grid: Vec<Cell>,
+    /// Decides which cells neighbour each other. Set it before populating the map.
+    pub topology: Topology,
+    stencil: Stencil,
+    /// The cells sharing an edge with each cell, built with `stencil`.
+    adjacency: Stencil,
 }
 
 impl Map {
     pub fn populate(&mut self, width: usize, height: usize, mines: usize) -> () {
+        use rand::Rng;
+
+        let seed = rand::thread_rng().gen::<u64>();
+        self.populate_seeded(width, height, 1, mines, seed);
+    }
+
+    /// Fill the map deterministically: the same dimensions, mine count and seed always produce
+    /// the same mines. Numbers are counted on the map's topology.
+    pub fn populate_seeded(&mut self, width: usize, height: usize, depth: usize, mines: usize,
+                           seed: u64) -> () {
+        use rand::{SeedableRng, XorShiftRng};
         use rand::distributions::{IndependentSample, Range};
 
-        let mut rng = rand::thread_rng();
+        let cells = width * height * depth;
+        assert!(mines < cells, "too many mines for a {}x{}x{} map", width, height, depth);
+        if let Err(e) = self.topology.check(width, height) {
+            panic!("{}", e);
+        }
+
+        // XorShift refuses an all-zero seed, so the last word is never zero.
+        let mut rng = XorShiftRng::from_seed([
+            seed as u32,
+            (seed >> 32) as u32,
+            0x9e37_79b9,
+            0x7f4a_7c15,
+        ]);
 
         self.width = width;
         self.height = height;
+        self.depth = depth;
         self.mines = mines;
 
-        let mut grid = vec![];
-        for _ in 0..width * height {
-            grid.push(Cell {
-                mine: false,
-                interaction: Interaction::Undiscovered,
-                nearby: 9,
-            });
-        }
+        self.stencil = self.topology.stencil(self.size());
+        self.adjacency = self.topology.adjacency(self.size());
+        self.grid = vec![Cell::default(); cells];
 
-        let range = Range::new(0, width * height - 1);
+        let range = Range::new(0, cells);
 
         let mut mines_filled_count = 0;
-        loop {
+        while mines_filled_count < mines {
             let i = range.ind_sample(&mut rng);
-            if !grid[i].mine {
+            if !self.grid[i].is_mine() {
                 mines_filled_count += 1;
-                grid[i].mine = true;
-            }
-            if mines_filled_count == mines {
-                break;
+                self.grid[i].0 |= MINE;
             }
         }
 
-        self.grid = grid;
+        self.count_nearby();
+    }
+
+    /// A map with mines in exactly the cells given, such as a board read from a file.
+    pub fn with_mines(width: usize, height: usize, depth: usize, topology: Topology,
+                      mines: &[Coord]) -> Map {
+        let mut map = Map {
+            width: width,
+            height: height,
+            depth: depth,
+            topology: topology,
+            ..Default::default()
+        };
+        map.stencil = topology.stencil(map.size());
+        map.adjacency = topology.adjacency(map.size());
+        map.grid = vec![Cell::default(); width * height * depth];
+        for &coord in mines {
+            let index = map.index(coord);
+            if !map.grid[index].is_mine() {
+                map.grid[index].0 |= MINE;
+                map.mines += 1;
+            }
+        }
+        map.count_nearby();
+        map
+    }
 
-        // calculate nearby cells
-        for row in 0..height {
-            for column in 0..width {
-                let nearbys = self.get_nearby_cells(column, row);
-                let nearby = nearbys.iter().map(|&(left, top)| {
-                    (&self).is_mine(left, top)
-                }).filter(|&val| { val }).count();
-                self.grid[row * width + column].nearby = nearby;
+    /// Count each mine into its neighbours' numbers, rather than counting around every cell.
+    fn count_nearby(&mut self) {
+        for cell in self.grid.iter_mut() {
+            cell.0 &= !NEARBY;
+        }
+        for index in 0..self.grid.len() {
+            if !self.grid[index].is_mine() {
+                continue;
+            }
+            for neighbour in self.stencil.neighbours(self.size().coord(index)) {
+                let index = self.index(neighbour);
+                self.grid[index].0 += 1;
             }
         }
+    }
+
+    /// Move any mines in `cells` to the first cells outside them, in index order, so that the
+    /// same map always moves its mines to the same places. Mines stay put if there is nowhere
+    /// to move them. Returns whether any moved.
+    pub fn move_mines_from(&mut self, cells: &[Coord]) -> bool {
+        let size = self.size();
+        let mut from: Vec<usize> = cells.iter().map(|&coord| size.index(coord))
+            .filter(|&index| self.grid[index].is_mine())
+            .collect();
+        // Wrapping boards can list a cell twice.
+        from.sort();
+        from.dedup();
+        let to: Vec<usize> = (0..self.grid.len())
+            .filter(|&index| !self.grid[index].is_mine() && !cells.contains(&size.coord(index)))
+            .take(from.len())
+            .collect();
+        if from.is_empty() || to.len() < from.len() {
+            return false;
+        }
+        for (&from, &to) in from.iter().zip(to.iter()) {
+            self.grid[from].0 &= !MINE;
+            self.grid[to].0 |= MINE;
+        }
+        self.count_nearby();
+        true
+    }
+
+    pub fn mines(&self) -> usize {
+        self.mines
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height, self.depth)
+    }
+
+    /// Position of a cell in `grid`.
+    pub fn index(&self, coord: Coord) -> usize {
+        self.size().index(coord)
+    }
 
+    pub fn cell(&self, coord: Coord) -> &Cell {
+        &self.grid[self.index(coord)]
     }
 
-    pub fn is_mine(&self, column: usize, row: usize) -> bool {
-        self.grid[row * self.width + column].mine
+    /// Every cell's coordinates, layer by layer and row by row.
+    pub fn coords(&self) -> Coords {
+        self.size().coords()
     }
 
-    pub fn get_nearby_cells(&self, column: usize, row: usize) -> Vec<(usize, usize)> {
-        let mut result = vec![];
-        if column > 0 {
-            result.push((column - 1, row));
-            if row > 0 {
-                result.push((column - 1, row - 1));
+    /// The cells in the box between `from` and `to`, both included.
+    pub fn region(&self, from: Coord, to: Coord) -> Coords {
+        self.size().region(from, to)
+    }
+
+    /// Every cell with its coordinates.
+    pub fn cells(&self) -> iter::Zip<Coords, slice::Iter<Cell>> {
+        self.coords().zip(self.grid.iter())
+    }
+
+    /// The rows of one layer, top to bottom.
+    pub fn rows(&self, layer: usize) -> slice::Chunks<Cell> {
+        let cells = self.width * self.height;
+        self.grid[layer * cells..(layer + 1) * cells].chunks(self.width)
+    }
+
+    /// A fingerprint of the board layout (topology, dimensions and mine positions), used to check
+    /// that two parties are talking about the same board. 64-bit FNV-1a.
+    pub fn board_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        {
+            let mut feed = |byte: u8| {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            };
+            // Single layer boards leave the depth out, so they hash as they did before layers.
+            let mut dimensions = vec![self.width as u64, self.height as u64];
+            if self.depth > 1 {
+                dimensions.push(self.depth as u64);
+            }
+            for &value in dimensions.iter() {
+                for shift in 0..8 {
+                    feed((value >> (shift * 8)) as u8);
+                }
             }
-            if row < self.height - 1 {
-                result.push((column - 1, row + 1));
+            for byte in self.topology.name().bytes() {
+                feed(byte);
+            }
+            for cell in self.grid.iter() {
+                feed(cell.is_mine() as u8);
             }
         }
-        if row > 0 {
-            result.push((column, row - 1));
+        hash
+    }
+
+    /// Every mine and number on the map, for debugging. Printing it gives the game away.
+    pub fn solution(&self) -> Solution {
+        Solution { map: self }
+    }
+
+    pub fn is_mine(&self, coord: Coord) -> bool {
+        self.cell(coord).is_mine()
+    }
+
+    /// The cells whose mines `coord`'s number counts.
+    pub fn neighbours(&self, coord: Coord) -> Neighbours {
+        self.stencil.neighbours(coord)
+    }
+
+    /// The neighbourhood of every cell, which `Board`s share.
+    pub fn stencil(&self) -> &Stencil {
+        &self.stencil
+    }
+
+    /// The cells sharing an edge with `coord`.
+    pub fn adjacents(&self, coord: Coord) -> Neighbours {
+        self.adjacency.neighbours(coord)
+    }
+
+    /// The cell one step from `coord` in `direction`, if there is one.
+    pub fn step(&self, coord: Coord, direction: Direction) -> Option<Coord> {
+        self.topology.step(coord, direction, self.size())
+    }
+
+    /// Open a single cell as the player would. Returns `true` if the cell held a mine.
+    pub fn open(&mut self, coord: Coord) -> bool {
+        let index = self.index(coord);
+        if !self.grid[index].is_closed() {
+            return false;
         }
-        if row < self.height - 1 {
-            result.push((column, row + 1));
+        if self.grid[index].is_mine() {
+            self.grid[index].set_interaction(Interaction::Opened);
+            return true;
         }
-        if column < self.width - 1 {
-            result.push((column + 1, row));
-            if row > 0 {
-                result.push((column + 1, row - 1));
+        self.reveal(coord);
+        false
+    }
+
+    /// Open a cell and, if it has no mines nearby, flood outwards through its neighbours.
+    /// Mines and flagged cells are never opened by the flood, but question marks are.
+    pub fn reveal(&mut self, coord: Coord) {
+        let mut pending = vec![coord];
+        while let Some(coord) = pending.pop() {
+            let index = self.index(coord);
+            let cell = self.grid[index];
+            if cell.is_mine() || !cell.is_closed() {
+                continue;
             }
-            if row < self.height - 1 {
-                result.push((column + 1 , row + 1));
+            self.grid[index].set_interaction(Interaction::Opened);
+            if cell.nearby() == 0 {
+                let size = self.size();
+                let grid = &self.grid;
+                pending.extend(self.stencil.neighbours(coord).filter(|&neighbour| {
+                    grid[size.index(neighbour)].is_closed()
+                }));
             }
         }
-        result
     }
 
-    pub fn get_adjacent_cells(&self, column: usize, row: usize) -> Vec<(usize, usize)> {
-        let mut result = vec![];
-        if column > 0 {
-            result.push((column - 1, row));
+    /// Open every unflagged neighbour of an opened cell whose number is already satisfied by
+    /// flags. Returns `true` if one of the opened neighbours held a mine.
+    pub fn chord(&mut self, coord: Coord) -> bool {
+        let cell = *self.cell(coord);
+        if cell.interaction() != Interaction::Opened {
+            return false;
         }
-        if row > 0 {
-            result.push((column, row - 1));
+        let flagged = self.neighbours(coord).filter(|&neighbour| {
+            self.cell(neighbour).interaction() == Interaction::Flagged
+        }).count();
+        if flagged != cell.nearby() {
+            return false;
         }
-        if row < self.height - 1 {
-            result.push((column, row + 1));
+        // Opening needs the map mutably, so the neighbours are looked up first.
+        let neighbours: Vec<Coord> = self.neighbours(coord).collect();
+        let mut exploded = false;
+        for neighbour in neighbours {
+            exploded |= self.open(neighbour);
         }
-        if column < self.width - 1 {
-            result.push((column + 1, row));
-        }
-        result
+        exploded
     }
 
-    pub fn reveal(&mut self, column: usize, row: usize) {
-        if self.is_mine(column, row) {
-            return ();
-        } else {
-            match self.grid[row * self.width + column].interaction {
-                Interaction::Opened => {
-                    return ();
-                },
-                Interaction::Undiscovered => {
-                    self.grid[row * self.width + column].interaction = Interaction::Opened;
-                },
-                _ => (),
-            };
+    /// Switch an undiscovered cell to flagged and back. Question marks are taken off, and opened
+    /// cells are left untouched.
+    pub fn toggle_flag(&mut self, coord: Coord) {
+        let index = self.index(coord);
+        let ref mut cell = self.grid[index];
+        let interaction = match cell.interaction() {
+            Interaction::Undiscovered => Interaction::Flagged,
+            Interaction::Flagged | Interaction::Questioned => Interaction::Undiscovered,
+            Interaction::Opened => Interaction::Opened,
         };
-        let adjacents = self.get_adjacent_cells(column, row);
-        for &(column, row) in adjacents.iter() {
-            self.reveal(column, row);
-        }
+        cell.set_interaction(interaction);
     }
-}
 
+    /// Switch an undiscovered cell to flagged, then to a question mark, then back.
+    pub fn cycle_mark(&mut self, coord: Coord) {
+        let index = self.index(coord);
+        let ref mut cell = self.grid[index];
+        let interaction = match cell.interaction() {
+            Interaction::Undiscovered => Interaction::Flagged,
+            Interaction::Flagged => Interaction::Questioned,
+            Interaction::Questioned => Interaction::Undiscovered,
+            Interaction::Opened => Interaction::Opened,
+        };
+        cell.set_interaction(interaction);
+    }
 
-impl fmt::Display for Map {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut repr = Vec::new();
-        for l in 0..self.width {
-            let mut line_repr = Vec::new();
-            for t in 0..self.height {
-                let n = self.width * l + t;
-                line_repr.push(format!("{} ", self.grid[n]));
+    /// Whether every cell without a mine has been opened.
+    pub fn is_cleared(&self) -> bool {
+        self.grid.iter().all(|cell| {
+            match cell.interaction() {
+                Interaction::Opened => !cell.is_mine(),
+                _ => cell.is_mine(),
             }
-            line_repr.push("\n".to_owned());
+        })
+    }
+
+    /// The board's 3BV ("Bechtel's Board Benchmark Value"): the minimum number of clicks needed
+    /// to clear it without flagging. Every connected opening counts once, every numbered cell
+    /// not bordering an opening counts once more.
+    pub fn three_bv(&self) -> usize {
+        let mut marked = vec![false; self.grid.len()];
+        let mut bv = 0;
 
-            let line = line_repr.join(" ");
-            repr.push(line);
+        for (coord, cell) in self.cells() {
+            let index = self.index(coord);
+            if marked[index] || cell.is_mine() || cell.nearby() != 0 {
+                continue;
+            }
+            bv += 1;
+            let mut pending = vec![coord];
+            marked[index] = true;
+            while let Some(coord) = pending.pop() {
+                for neighbour in self.neighbours(coord) {
+                    let index = self.index(neighbour);
+                    if marked[index] {
+                        continue;
+                    }
+                    marked[index] = true;
+                    if self.grid[index].nearby() == 0 {
+                        pending.push(neighbour);
+                    }
+                }
+            }
         }
-        write!(f, "===== Map =====\n{}", repr.join("\n"))
+
+        bv + self.grid.iter().zip(marked.iter()).filter(|&(cell, &marked)| {
+            !marked && !cell.is_mine()
+        }).count()
+    }
+}
+
+/// The map as the player sees it. Use `solution` to see the mines.
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Board::from_map(self))
     }
 }
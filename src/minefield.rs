@@ -0,0 +1,96 @@
+//! Boards saved as text: where the mines are, without anything the player has done.
+//!
+//! `MineSweeper generate` writes these and `MineSweeper solve` reads them. Each row of cells is
+//! a line of `.` for a safe cell and `*` for a mine, and the layers of a layered board follow
+//! one another with a blank line between them. A `topology` line may come first, and lines
+//! starting with `#` are comments:
+//!
+//! ```text
+//! # seed 1234567890
+//! topology hex
+//! .*...
+//! .....
+//! ...*.
+//! ```
+
+use coord::Coord;
+use map::Map;
+use topology::Topology;
+
+/// The board's text, with `comment` on the first line if there is one.
+pub fn to_text(map: &Map, comment: Option<&str>) -> String {
+    let mut text = String::new();
+    if let Some(comment) = comment {
+        text.push_str(&format!("# {}\n", comment));
+    }
+    text.push_str(&format!("topology {}\n", map.topology.name()));
+    for layer in 0..map.depth {
+        if layer > 0 {
+            text.push('\n');
+        }
+        for row in map.rows(layer) {
+            text.extend(row.iter().map(|cell| if cell.is_mine() { '*' } else { '.' }));
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Read a board, whose cells are all closed.
+pub fn parse(text: &str) -> Result<Map, String> {
+    let mut topology = Topology::default();
+    let mut layers: Vec<Vec<&str>> = vec![vec![]];
+    let mut started = false;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with("topology ") && !started {
+            topology = try!(Topology::from_name(line["topology ".len()..].trim())
+                .map_err(|e| format!("line {}: {}", number + 1, e)));
+            continue;
+        }
+        if line.is_empty() {
+            // A blank line ends a layer, unless it's before the first or after another.
+            if started && !layers.last().unwrap().is_empty() {
+                layers.push(vec![]);
+            }
+            continue;
+        }
+        if let Some(c) = line.chars().find(|&c| c != '.' && c != '*') {
+            return Err(format!("line {}: expected only `.` and `*`, found `{}`", number + 1, c));
+        }
+        started = true;
+        layers.last_mut().unwrap().push(line);
+    }
+    if layers.last().map_or(false, |layer| layer.is_empty()) {
+        layers.pop();
+    }
+
+    let height = layers.first().map_or(0, |layer| layer.len());
+    let width = layers.first().and_then(|layer| layer.first()).map_or(0, |row| row.len());
+    if width == 0 {
+        return Err("the board is empty".to_owned());
+    }
+    let mut mines = vec![];
+    for (l, layer) in layers.iter().enumerate() {
+        if layer.len() != height {
+            return Err(format!("layer {} has {} rows, not {}", l + 1, layer.len(), height));
+        }
+        for (row, line) in layer.iter().enumerate() {
+            if line.len() != width {
+                return Err(format!("row {} of layer {} has {} cells, not {}", row + 1, l + 1,
+                                   line.len(), width));
+            }
+            mines.extend(line.char_indices()
+                .filter(|&(_, c)| c == '*')
+                .map(|(column, _)| Coord::new(column, row, l)));
+        }
+    }
+    if mines.len() == width * height * layers.len() {
+        return Err("every cell holds a mine".to_owned());
+    }
+    try!(topology.check(width, height));
+    Ok(Map::with_mines(width, height, layers.len(), topology, &mines))
+}
@@ -0,0 +1,246 @@
+//! An overview of the whole board for boards too large to see at once.
+//!
+//! The board is shrunk into blocks of cells, each coloured by what most of its cells are (a mine
+//! that went off colours its block whatever the rest), and runs of blocks of the same colour are
+//! drawn as one rectangle. Every tiling is drawn as a square grid. A frame marks the part of the
+//! board the `BoardView` shows, and clicking or dragging on the minimap moves it there.
+
+use std::cmp;
+
+use conrod::{
+    Backend,
+    Color,
+    Colorable,
+    CommonBuilder,
+    IndexSlot,
+    Positionable,
+    Rectangle,
+    Scalar,
+    UpdateArgs,
+    Widget,
+    WidgetKind,
+};
+use conrod::color;
+
+use minesweeper::board::{Board, Tile};
+use minesweeper::coord::Coord;
+
+/// A `&'static str` that can be used to uniquely identify our widget type.
+pub const KIND: WidgetKind = "Minimap";
+
+/// The most blocks drawn along either side.
+const MAX_BLOCKS: usize = 64;
+
+/// The minimap of one layer of a board.
+pub struct Minimap<'a, F> {
+    common: CommonBuilder,
+    board: &'a Board,
+    layer: usize,
+    /// The part of the board shown, as `Viewport::window` gives it.
+    window: [Scalar; 4],
+    /// Called with the point of the board clicked, as fractions of its width and height.
+    maybe_react: Option<F>,
+}
+
+/// Represents the unique, cached state for the minimap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    /// Whether the left button went down on the minimap and is still held.
+    dragging: bool,
+    /// One rectangle per run of blocks, growing with the most runs ever drawn.
+    runs: Vec<IndexSlot>,
+    window_idx: IndexSlot,
+}
+
+/// Represents the unique styling for the minimap. It has none yet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style;
+
+/// What a block of cells is mostly.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Block {
+    Unknown,
+    Opened,
+    Flagged,
+    Exploded,
+}
+
+impl Block {
+    fn color(&self) -> Color {
+        match *self {
+            Block::Unknown => color::rgb(0.5, 0.5, 0.5),
+            Block::Opened => color::rgb(0.85, 0.85, 0.85),
+            Block::Flagged => color::rgb(0.95, 0.8, 0.2),
+            Block::Exploded => color::rgb(0.9, 0.2, 0.2),
+        }
+    }
+}
+
+/// Sort the cells of one layer into blocks of `side` by `side` cells, row by row.
+fn blocks(board: &Board, layer: usize, side: usize) -> Vec<Vec<Block>> {
+    let columns = (board.width + side - 1) / side;
+    let rows = (board.height + side - 1) / side;
+    (0..rows).map(|block_row| {
+        (0..columns).map(|block_column| {
+            // Unknown, opened and flagged cells, and whether a mine went off.
+            let mut counts = [0, 0, 0];
+            let mut exploded = false;
+            let from = Coord::new(block_column * side, block_row * side, layer);
+            let to = Coord::new(from.column + side - 1, from.row + side - 1, layer);
+            for coord in board.size().region(from, to) {
+                match board.tile(coord) {
+                    Tile::Unknown | Tile::Questioned => counts[0] += 1,
+                    Tile::Number(_) => counts[1] += 1,
+                    Tile::Flagged => counts[2] += 1,
+                    Tile::Mine => exploded = true,
+                }
+            }
+            if exploded {
+                Block::Exploded
+            } else if counts[2] > counts[0] && counts[2] > counts[1] {
+                Block::Flagged
+            } else if counts[1] > counts[0] {
+                Block::Opened
+            } else {
+                Block::Unknown
+            }
+        }).collect()
+    }).collect()
+}
+
+impl<'a, F> Minimap<'a, F> {
+    pub fn new(board: &'a Board, layer: usize) -> Minimap<'a, F> {
+        Minimap {
+            common: CommonBuilder::new(),
+            board: board,
+            layer: layer,
+            window: [0.0, 0.0, 1.0, 1.0],
+            maybe_react: None,
+        }
+    }
+
+    /// Frame this part of the board, `[left, top, right, bottom]` as fractions of its width and
+    /// height.
+    pub fn window(mut self, window: [Scalar; 4]) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Set the reaction to clicking or dragging on the minimap.
+    pub fn react(mut self, reaction: F) -> Self {
+        self.maybe_react = Some(reaction);
+        self
+    }
+}
+
+impl<'a, F> Widget for Minimap<'a, F>
+    where F: FnMut([Scalar; 2])
+{
+    type State = State;
+    type Style = Style;
+
+    fn common(&self) -> &CommonBuilder {
+        &self.common
+    }
+
+    fn common_mut(&mut self) -> &mut CommonBuilder {
+        &mut self.common
+    }
+
+    fn unique_kind(&self) -> &'static str {
+        KIND
+    }
+
+    fn init_state(&self) -> State {
+        State {
+            dragging: false,
+            runs: Vec::new(),
+            window_idx: IndexSlot::new(),
+        }
+    }
+
+    fn style(&self) -> Style {
+        Style
+    }
+
+    fn update<B: Backend>(mut self, args: UpdateArgs<Self, B>) {
+        use conrod::MouseButtonPosition::Down;
+
+        let UpdateArgs { idx, state, rect, mut ui, .. } = args;
+        let board = self.board;
+
+        // Blocks are square, and the map keeps the board's proportions.
+        let side = cmp::max((cmp::max(board.width, board.height) + MAX_BLOCKS - 1) / MAX_BLOCKS, 1);
+        let cell = (rect.w() / board.width as Scalar).min(rect.h() / board.height as Scalar);
+        let (map_w, map_h) = (cell * board.width as Scalar, cell * board.height as Scalar);
+        let (left, top) = (rect.x() - map_w / 2.0, rect.y() + map_h / 2.0);
+
+        // Clicking moves the view there, and so does dragging while the button is held.
+        let maybe_mouse = ui.input(idx).maybe_mouse;
+        let dragging = maybe_mouse.map_or(false, |mouse| mouse.left.position == Down);
+        if let Some(mouse) = maybe_mouse {
+            if dragging {
+                let x = ((mouse.xy[0] - left) / map_w).max(0.0).min(1.0);
+                let y = ((top - mouse.xy[1]) / map_h).max(0.0).min(1.0);
+                if let Some(ref mut react) = self.maybe_react {
+                    react([x, y]);
+                }
+            }
+        }
+        match (state.view().dragging, dragging) {
+            (false, true) => ui.capture_mouse(idx),
+            (true, false) => ui.uncapture_mouse(idx),
+            _ => (),
+        }
+
+        // Runs of blocks with the same colour, as (row, first column, columns, block).
+        let mut runs = vec![];
+        for (row, blocks) in blocks(board, self.layer, side).into_iter().enumerate() {
+            let mut start = 0;
+            for column in 1..blocks.len() + 1 {
+                if column == blocks.len() || blocks[column] != blocks[start] {
+                    runs.push((row, start, column - start, blocks[start]));
+                    start = column;
+                }
+            }
+        }
+
+        if state.view().dragging != dragging || state.view().runs.len() < runs.len() {
+            state.update(|state| {
+                state.dragging = dragging;
+                let missing = runs.len().saturating_sub(state.runs.len());
+                state.runs.extend((0..missing).map(|_| IndexSlot::new()));
+            });
+        }
+
+        let block = cell * side as Scalar;
+        for (&(row, column, length, kind), slot) in runs.iter().zip(state.view().runs.iter()) {
+            // The last blocks may reach past the board.
+            let x = left + column as Scalar * block;
+            let y = top - row as Scalar * block;
+            let w = (block * length as Scalar).min(left + map_w - x);
+            let h = block.min(y - (top - map_h));
+            let slot_idx = slot.get(&mut ui);
+            Rectangle::fill([w, h])
+                .x_y(x + w / 2.0, y - h / 2.0)
+                .parent(idx)
+                .graphics_for(idx)
+                .color(kind.color())
+                .set(slot_idx, &mut ui);
+        }
+
+        // The frame is cut to the map, so it stays visible however far the view is panned.
+        let window = self.window;
+        let x0 = left + window[0].max(0.0) * map_w;
+        let x1 = left + window[2].min(1.0) * map_w;
+        let y0 = top - window[1].max(0.0) * map_h;
+        let y1 = top - window[3].min(1.0) * map_h;
+        let window_idx = state.view().window_idx.get(&mut ui);
+        Rectangle::outline([(x1 - x0).max(1.0), (y0 - y1).max(1.0)])
+            .x_y((x0 + x1) / 2.0, (y0 + y1) / 2.0)
+            .parent(idx)
+            .graphics_for(idx)
+            .color(color::rgb(0.1, 0.4, 0.9))
+            .set(window_idx, &mut ui);
+    }
+}
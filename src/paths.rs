@@ -0,0 +1,49 @@
+//! Where the game keeps its files on disk.
+
+use std::env;
+use std::path::PathBuf;
+
+const APP_DIR: &'static str = "minesweeper";
+
+/// The per-user data directory, e.g. `~/.local/share/minesweeper` on Linux.
+///
+/// Follows `XDG_DATA_HOME` (or `APPDATA` on Windows) and falls back to the home directory.
+/// Returns `None` only if no home directory can be determined at all.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_DATA_HOME") {
+        return Some(PathBuf::from(dir).join(APP_DIR));
+    }
+    if cfg!(windows) {
+        if let Some(dir) = env::var_os("APPDATA") {
+            return Some(PathBuf::from(dir).join(APP_DIR));
+        }
+    }
+    env::home_dir().map(|home| {
+        if cfg!(target_os = "macos") {
+            home.join("Library").join("Application Support").join(APP_DIR)
+        } else {
+            home.join(".local").join("share").join(APP_DIR)
+        }
+    })
+}
+
+/// The per-user configuration directory, e.g. `~/.config/minesweeper` on Linux.
+///
+/// Follows `XDG_CONFIG_HOME` (or `APPDATA` on Windows) and falls back to the home directory.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME") {
+        return Some(PathBuf::from(dir).join(APP_DIR));
+    }
+    if cfg!(windows) {
+        if let Some(dir) = env::var_os("APPDATA") {
+            return Some(PathBuf::from(dir).join(APP_DIR));
+        }
+    }
+    env::home_dir().map(|home| {
+        if cfg!(target_os = "macos") {
+            home.join("Library").join("Preferences").join(APP_DIR)
+        } else {
+            home.join(".config").join(APP_DIR)
+        }
+    })
+}
@@ -0,0 +1,310 @@
+//! Boards as text, for the terminal, logs and tests.
+//!
+//! A `Renderer` draws one of three views: what the player sees (`player`), where everything is
+//! (`solution`) and both side by side with wrong flags marked (`diff`). The last two know where
+//! the mines are and are meant for debugging. Drawing can add 1-based row and column rulers,
+//! frame the board with Unicode box-drawing characters or plain ASCII, and colour the cells.
+//!
+//! Every cell takes two columns, so hex boards shift their odd rows by one to interlock them.
+//! Layered boards are drawn one layer after another, each under a `layer N/DEPTH` heading.
+
+use std::iter;
+
+use ansi_term::Colour::{Blue, Cyan, Green, Purple, Red, White, Yellow};
+use ansi_term::Style;
+
+use board::{Board, Tile};
+use coord::Coord;
+use map::{Interaction, Map};
+use topology::{Tiling, Topology};
+
+/// What is drawn in one cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Glyph {
+    Tile(Tile),
+    /// A flag on a cell without a mine, only shown by `diff`.
+    WrongFlag,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Renderer {
+    rulers: bool,
+    unicode: bool,
+    colour: bool,
+    dimmed: bool,
+}
+
+impl Renderer {
+    /// Plain ASCII without rulers or colour, which is what the `Display` impls use.
+    pub fn new() -> Renderer {
+        Renderer {
+            rulers: false,
+            unicode: false,
+            colour: false,
+            dimmed: false,
+        }
+    }
+
+    /// Number the rows and columns from 1.
+    pub fn rulers(mut self, rulers: bool) -> Self {
+        self.rulers = rulers;
+        self
+    }
+
+    /// Draw the frame and cells with Unicode characters instead of ASCII.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    /// Colour the cells with ANSI escape codes.
+    pub fn colour(mut self, colour: bool) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    /// Draw every cell faintly, for boards shown only as context. Needs `colour`.
+    pub fn dimmed(mut self, dimmed: bool) -> Self {
+        self.dimmed = dimmed;
+        self
+    }
+
+    /// What the player sees.
+    pub fn player(&self, board: &Board) -> String {
+        stack(board.depth, |layer| self.player_layer(board, layer))
+    }
+
+    /// Every mine and number.
+    pub fn solution(&self, map: &Map) -> String {
+        stack(map.depth, |layer| self.solution_layer(map, layer))
+    }
+
+    /// The player's view next to the solution, with flags on cells without a mine marked.
+    pub fn diff(&self, map: &Map) -> String {
+        let board = Board::from_map(map);
+        stack(map.depth, |layer| {
+            let player = self.layer(map.width, map.height, map.topology, |column, row| {
+                let coord = Coord::new(column, row, layer);
+                let cell = map.cell(coord);
+                match cell.interaction() {
+                    Interaction::Flagged if !cell.is_mine() => Glyph::WrongFlag,
+                    _ => Glyph::Tile(board.tile(coord)),
+                }
+            });
+            let solution = self.solution_layer(map, layer);
+            side_by_side(&[titled("player", player), titled("solution", solution)], "   ")
+        })
+    }
+
+    /// One layer of the player's view, one string per line.
+    pub fn player_layer(&self, board: &Board, layer: usize) -> Vec<String> {
+        self.layer(board.width, board.height, board.topology, |column, row| {
+            Glyph::Tile(board.tile(Coord::new(column, row, layer)))
+        })
+    }
+
+    fn solution_layer(&self, map: &Map, layer: usize) -> Vec<String> {
+        self.layer(map.width, map.height, map.topology, |column, row| {
+            let cell = map.cell(Coord::new(column, row, layer));
+            Glyph::Tile(if cell.is_mine() { Tile::Mine } else { Tile::Number(cell.nearby()) })
+        })
+    }
+
+    fn layer<F>(&self, width: usize, height: usize, topology: Topology, glyph: F) -> Vec<String>
+        where F: Fn(usize, usize) -> Glyph
+    {
+        let hex = topology.tiling == Tiling::Hex;
+        let inner = 2 * width + 1 + hex as usize;
+        let (horizontal, vertical, corners) = if self.unicode {
+            ("─", "│", ["┌", "┐", "└", "┘"])
+        } else {
+            ("-", "|", ["+", "+", "+", "+"])
+        };
+        let margin = if self.rulers { "    " } else { "" };
+        let mut lines = vec![];
+
+        if self.rulers {
+            // Two digit column numbers are written downwards, tens above units.
+            if width >= 10 {
+                let tens: String = (1..width + 1).map(|column| {
+                    if column >= 10 { format!(" {}", column / 10 % 10) } else { "  ".to_owned() }
+                }).collect();
+                lines.push(format!("{} {}", margin, tens));
+            }
+            let units: String = (1..width + 1).map(|column| format!(" {}", column % 10)).collect();
+            lines.push(format!("{} {}", margin, units));
+        }
+
+        let rule = repeat(horizontal, inner);
+        lines.push(format!("{}{}{}{}", margin, corners[0], rule, corners[1]));
+        for row in 0..height {
+            let mut line = if self.rulers { format!("{:>3} ", row + 1) } else { String::new() };
+            line.push_str(vertical);
+            let shifted = hex && row % 2 == 1;
+            if shifted {
+                line.push(' ');
+            }
+            for column in 0..width {
+                line.push(' ');
+                line.push_str(&self.paint(glyph(column, row)));
+            }
+            line.push(' ');
+            if hex && !shifted {
+                line.push(' ');
+            }
+            line.push_str(vertical);
+            lines.push(line);
+        }
+        lines.push(format!("{}{}{}{}", margin, corners[2], rule, corners[3]));
+        lines
+    }
+
+    fn paint(&self, glyph: Glyph) -> String {
+        let symbol = if self.unicode {
+            match glyph {
+                Glyph::Tile(Tile::Unknown) => '■',
+                Glyph::Tile(Tile::Flagged) => '⚑',
+                Glyph::Tile(Tile::Number(0)) => '·',
+                Glyph::Tile(Tile::Mine) => '✱',
+                Glyph::Tile(tile) => tile.symbol(),
+                Glyph::WrongFlag => '✗',
+            }
+        } else {
+            match glyph {
+                Glyph::Tile(tile) => tile.symbol(),
+                Glyph::WrongFlag => 'X',
+            }
+        };
+        if !self.colour {
+            return symbol.to_string();
+        }
+
+        let style = match glyph {
+            Glyph::Tile(Tile::Unknown) | Glyph::Tile(Tile::Number(0)) => Style::new().dimmed(),
+            Glyph::Tile(Tile::Flagged) => Yellow.bold(),
+            Glyph::Tile(Tile::Questioned) => Yellow.normal(),
+            Glyph::Tile(Tile::Mine) | Glyph::WrongFlag => Red.bold(),
+            Glyph::Tile(Tile::Number(n)) => match n {
+                1 => Blue.normal(),
+                2 => Green.normal(),
+                3 => Red.normal(),
+                4 => Purple.normal(),
+                5 => Yellow.normal(),
+                6 => Cyan.normal(),
+                _ => White.bold(),
+            },
+        };
+        let style = if self.dimmed { style.dimmed() } else { style };
+        format!("{}", style.paint(symbol.to_string()))
+    }
+}
+
+/// Put blocks of lines next to each other, padding each to its widest line.
+pub fn side_by_side(blocks: &[Vec<String>], gap: &str) -> Vec<String> {
+    let widths: Vec<usize> = blocks.iter().map(|block| {
+        block.iter().map(|line| visible_width(line)).max().unwrap_or(0)
+    }).collect();
+    let height = blocks.iter().map(|block| block.len()).max().unwrap_or(0);
+    (0..height).map(|i| {
+        let parts: Vec<String> = blocks.iter().zip(widths.iter()).map(|(block, &width)| {
+            let line = block.get(i).map_or("", |line| line.as_str());
+            format!("{}{}", line, repeat(" ", width - visible_width(line)))
+        }).collect();
+        parts.join(gap).trim_end().to_owned()
+    }).collect()
+}
+
+fn repeat(text: &str, times: usize) -> String {
+    iter::repeat(text).take(times).collect()
+}
+
+/// Characters on the screen, not counting ANSI escape codes.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut escaped = false;
+    for c in line.chars() {
+        match (escaped, c) {
+            (false, '\x1b') => escaped = true,
+            (false, _) => width += 1,
+            (true, 'm') => escaped = false,
+            (true, _) => (),
+        }
+    }
+    width
+}
+
+fn titled(title: &str, mut block: Vec<String>) -> Vec<String> {
+    block.insert(0, title.to_owned());
+    block
+}
+
+/// Draw every layer, headed by its number when there is more than one.
+fn stack<F>(depth: usize, layer: F) -> String
+    where F: Fn(usize) -> Vec<String>
+{
+    let mut text = String::new();
+    for l in 0..depth {
+        if l > 0 {
+            text.push('\n');
+        }
+        if depth > 1 {
+            text.push_str(&format!("layer {}/{}\n", l + 1, depth));
+        }
+        for line in layer(l) {
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Renderer;
+    use board::Board;
+    use coord::Coord;
+    use map::Map;
+    use minefield;
+
+    /// A mine in the top left corner, everything else opened but a wrong flag below the mine.
+    fn played() -> Map {
+        let mut map = minefield::parse("*..\n...\n").unwrap();
+        map.open(Coord::new(2, 1, 0));
+        map.toggle_flag(Coord::new(0, 1, 0));
+        map
+    }
+
+    #[test]
+    fn player() {
+        assert_eq!(Renderer::new().player(&Board::from_map(&played())),
+                   "+-------+\n| # 1 . |\n| F 1 . |\n+-------+\n");
+    }
+
+    #[test]
+    fn solution() {
+        assert_eq!(Renderer::new().solution(&played()),
+                   "+-------+\n| * 1 . |\n| 1 1 . |\n+-------+\n");
+    }
+
+    #[test]
+    fn diff() {
+        assert_eq!(Renderer::new().diff(&played()),
+                   "player      solution\n\
+                    +-------+   +-------+\n\
+                    | # 1 . |   | * 1 . |\n\
+                    | X 1 . |   | 1 1 . |\n\
+                    +-------+   +-------+\n");
+    }
+
+    #[test]
+    fn rulers() {
+        assert_eq!(Renderer::new().rulers(true).player(&Board::from_map(&played())),
+                   "      1 2 3\n    +-------+\n  1 | # 1 . |\n  2 | F 1 . |\n    +-------+\n");
+    }
+
+    #[test]
+    fn hex_rows_shift() {
+        let map = minefield::parse("topology hex\n..\n..\n").unwrap();
+        assert_eq!(Renderer::new().solution(&map), "+------+\n| . .  |\n|  . . |\n+------+\n");
+    }
+}
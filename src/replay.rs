@@ -0,0 +1,274 @@
+//! Recordings of whole games, precise enough to re-play them move for move.
+//!
+//! A replay is plain text:
+//!
+//! ```text
+//! minesweeper-replay 1
+//! board expert 30 16 99 1234567890 square 1
+//! hash 8c3f0e56a1d2b4f7
+//! 0 open 3 4
+//! 812 flag 5 4
+//! ```
+//!
+//! The board line holds the preset, width, height, mine count, seed, topology, number of layers
+//! and first click policy (older replays may leave out the last three, and are square, flat and
+//! leave the first click to chance; the policy is only written when it isn't `any`); the hash is
+//! `Map::board_hash` of the board those produce. Every following line is one move: the time in
+//! milliseconds since the first click, the action, and the cell's column, row and layer (which
+//! flat boards may leave out).
+
+use std::fmt;
+
+use coord::Coord;
+use game::{Action, FirstClick, Game, Move, Preset, Status};
+use topology::Topology;
+
+const MAGIC: &'static str = "minesweeper-replay 1";
+
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub preset: Preset,
+    pub topology: Topology,
+    pub seed: u64,
+    pub first_click: FirstClick,
+    pub board_hash: u64,
+    pub moves: Vec<Move>,
+}
+
+impl Replay {
+    pub fn from_game(game: &Game) -> Replay {
+        // The hash is of the board the seed deals, before any mines move for the first click.
+        let board_hash = match game.first_click {
+            FirstClick::Any => game.map.board_hash(),
+            _ => Game::new(game.preset, game.topology, game.seed).map.board_hash(),
+        };
+        Replay {
+            preset: game.preset,
+            topology: game.topology,
+            seed: game.seed,
+            first_click: game.first_click,
+            board_hash: board_hash,
+            moves: game.moves.clone(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Replay, String> {
+        let mut lines = text.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+
+        if lines.next() != Some(MAGIC) {
+            return Err("not a replay (missing header)".to_owned());
+        }
+
+        let board: Vec<&str> = try!(lines.next().ok_or("missing board line")).split(' ').collect();
+        if board.len() < 6 || board.len() > 9 || board[0] != "board" {
+            return Err("malformed board line".to_owned());
+        }
+        let width = try!(parse_number(board[2], "width"));
+        let height = try!(parse_number(board[3], "height"));
+        let mines = try!(parse_number(board[4], "mine count"));
+        let seed = try!(board[5].parse::<u64>().map_err(|_| "malformed seed".to_owned()));
+        let depth = match board.get(7) {
+            Some(field) => try!(parse_number(field, "depth")),
+            None => 1,
+        };
+        let preset = try!(Preset::from_name(board[1], width, height, depth, mines)
+            .ok_or(format!("unknown preset: {}", board[1])));
+        if preset.dimensions() != (width, height, mines) || preset.depth() != depth {
+            return Err(format!("dimensions don't match the {} preset", board[1]));
+        }
+        try!(preset.check());
+        let topology = match board.get(6) {
+            Some(name) => try!(Topology::from_name(name)),
+            None => Topology::default(),
+        };
+        try!(topology.check(width, height));
+        let first_click = match board.get(8) {
+            Some(name) => try!(FirstClick::from_name(name)
+                .ok_or(format!("unknown first click policy: {}", name))),
+            None => FirstClick::Any,
+        };
+        let hash_line = try!(lines.next().ok_or("missing hash line"));
+        if !hash_line.starts_with("hash ") {
+            return Err("malformed hash line".to_owned());
+        }
+        let board_hash = try!(u64::from_str_radix(&hash_line[5..], 16)
+            .map_err(|_| "malformed hash".to_owned()));
+
+        let mut moves = vec![];
+        for (i, line) in lines.enumerate() {
+            let fields: Vec<&str> = line.split(' ').collect();
+            let step = if fields.len() == 4 || fields.len() == 5 {
+                match (fields[0].parse(), Action::from_name(fields[1]), fields[2].parse(),
+                       fields[3].parse(), fields.get(4).unwrap_or(&"0").parse()) {
+                    (Ok(time_ms), Some(action), Ok(column), Ok(row), Ok(layer)) => Some(Move {
+                        time_ms: time_ms,
+                        action: action,
+                        coord: Coord::new(column, row, layer),
+                    }),
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            match step {
+                Some(step) => moves.push(step),
+                None => return Err(format!("malformed move {}: {}", i + 1, line)),
+            }
+        }
+
+        Ok(Replay {
+            preset: preset,
+            topology: topology,
+            seed: seed,
+            first_click: first_click,
+            board_hash: board_hash,
+            moves: moves,
+        })
+    }
+
+    /// Regenerate the board from the seed and play every move on it, returning the game as it
+    /// stands after the last one.
+    pub fn play(&self) -> Game {
+        let mut game = self.new_game();
+        for &step in self.moves.iter() {
+            game.apply(step);
+        }
+        game
+    }
+
+    /// Check that the replay is an honest win: the seed produces the board it claims, the moves
+    /// are in time order, and playing them clears the board with the last move. Returns the
+    /// winning time in milliseconds.
+    pub fn verify(&self) -> Result<u64, String> {
+        let mut game = self.new_game();
+        if game.map.board_hash() != self.board_hash {
+            return Err("board hash doesn't match the seed".to_owned());
+        }
+
+        let mut last_time = 0;
+        for (i, &step) in self.moves.iter().enumerate() {
+            if step.time_ms < last_time {
+                return Err(format!("move {} goes back in time", i + 1));
+            }
+            if game.is_over() {
+                return Err(format!("move {} was made after the game ended", i + 1));
+            }
+            last_time = step.time_ms;
+            game.apply(step);
+        }
+
+        match game.status {
+            Status::Finished => Ok(last_time),
+            Status::Failed => Err("the replay hits a mine".to_owned()),
+            Status::Playing => Err("the replay doesn't clear the board".to_owned()),
+        }
+    }
+
+    /// The game before the first move.
+    fn new_game(&self) -> Game {
+        let mut game = Game::new(self.preset, self.topology, self.seed);
+        game.first_click = self.first_click;
+        game
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (width, height, mines) = self.preset.dimensions();
+        try!(writeln!(f, "{}", MAGIC));
+        try!(write!(f, "board {} {} {} {} {} {} {}", self.preset.name(), width, height, mines,
+                    self.seed, self.topology.name(), self.preset.depth()));
+        if self.first_click != FirstClick::Any {
+            try!(write!(f, " {}", self.first_click.name()));
+        }
+        try!(writeln!(f, ""));
+        try!(writeln!(f, "hash {:016x}", self.board_hash));
+        for step in self.moves.iter() {
+            try!(writeln!(f, "{} {} {} {} {}", step.time_ms, step.action.name(),
+                          step.coord.column, step.coord.row, step.coord.layer));
+        }
+        Ok(())
+    }
+}
+
+fn parse_number(field: &str, what: &str) -> Result<usize, String> {
+    field.parse().map_err(|_| format!("malformed {}", what))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::Replay;
+    use coord::Coord;
+    use game::{FirstClick, Game, Preset};
+    use topology::Topology;
+
+    /// A win on `seed`, opening every safe cell in turn a tenth of a second apart. The
+    /// leaderboard's tests submit these too.
+    pub fn win(preset: Preset, seed: u64) -> Replay {
+        let mut game = Game::new(preset, Topology::default(), seed);
+        let safe: Vec<Coord> = game.map.cells().filter(|&(_, cell)| !cell.is_mine())
+            .map(|(coord, _)| coord)
+            .collect();
+        for coord in safe {
+            game.open(coord);
+        }
+        let mut replay = Replay::from_game(&game);
+        for (i, step) in replay.moves.iter_mut().enumerate() {
+            step.time_ms = i as u64 * 100;
+        }
+        replay
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut game = Game::new(Preset::Custom { width: 6, height: 4, depth: 2, mines: 7 },
+                                 Topology::from_name("hex-torus").unwrap(), 99);
+        game.first_click = FirstClick::Opening;
+        game.open(Coord::new(2, 3, 1));
+        game.toggle_flag(Coord::new(0, 0, 0));
+        let text = Replay::from_game(&game).to_string();
+        let replay = Replay::parse(&text).unwrap();
+        assert_eq!(replay.first_click, FirstClick::Opening);
+        assert_eq!(replay.moves, game.moves);
+        assert_eq!(replay.to_string(), text);
+    }
+
+    #[test]
+    fn older_replays() {
+        let replay = Replay::parse("minesweeper-replay 1\nboard beginner 9 9 10 5\n\
+                                    hash 00000000000000ff\n0 open 3 4\n").unwrap();
+        assert_eq!(replay.topology, Topology::default());
+        assert_eq!(replay.first_click, FirstClick::Any);
+        assert_eq!(replay.moves[0].coord, Coord::new(3, 4, 0));
+    }
+
+    #[test]
+    fn bad_replays() {
+        assert!(Replay::parse("board beginner 9 9 10 5\nhash 0\n").is_err());
+        assert!(Replay::parse("minesweeper-replay 1\nboard beginner 9 9 11 5\nhash 0\n")
+            .is_err());
+        assert!(Replay::parse("minesweeper-replay 1\nboard custom 100000 100000 1 5\nhash 0\n")
+            .is_err());
+        assert!(Replay::parse("minesweeper-replay 1\nboard beginner 9 9 10 5\nhash 0\n\
+                               0 dig 1 1\n").is_err());
+    }
+
+    #[test]
+    fn verify() {
+        let replay = win(Preset::Beginner, 3);
+        let last = replay.moves.last().unwrap().time_ms;
+        assert_eq!(replay.verify(), Ok(last));
+
+        let mut forged = replay.clone();
+        forged.board_hash ^= 1;
+        assert!(forged.verify().is_err());
+
+        let mut unfinished = replay.clone();
+        unfinished.moves.pop();
+        assert!(unfinished.verify().is_err());
+
+        let mut reordered = replay.clone();
+        reordered.moves[1].time_ms = last + 1;
+        assert!(reordered.verify().is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! The screens of the GUI and how the player moves between them.
+//!
+//! The screens form a stack with the main menu at the bottom. Opening a screen puts it on top,
+//! and Esc or a back button takes it off again, so going back always returns to where the player
+//! came from; Esc on the main menu quits. Starting or continuing a game leaves only the menu
+//! under it, so that backing out of a game goes through the pause screen to the menu.
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Screen {
+    Menu,
+    /// Picking the difficulty and topology of a new game.
+    NewGame,
+    Game,
+    /// The menu over a paused game, hiding its board.
+    Pause,
+    Stats,
+    Settings,
+    About,
+}
+
+impl Screen {
+    /// What the header says over the screen.
+    pub fn title(&self) -> &'static str {
+        match *self {
+            Screen::Menu | Screen::Game => "Minesweeper",
+            Screen::NewGame => "New game",
+            Screen::Pause => "Paused",
+            Screen::Stats => "Statistics",
+            Screen::Settings => "Settings",
+            Screen::About => "About",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Screens {
+    stack: Vec<Screen>,
+}
+
+impl Screens {
+    /// Start on the main menu.
+    pub fn new() -> Screens {
+        Screens { stack: vec![Screen::Menu] }
+    }
+
+    /// The screen shown.
+    pub fn current(&self) -> Screen {
+        *self.stack.last().unwrap()
+    }
+
+    /// Show `screen` over the current one. A screen already on the stack is gone back to
+    /// instead, so the stack never holds a screen twice.
+    pub fn open(&mut self, screen: Screen) {
+        match self.stack.iter().position(|&s| s == screen) {
+            Some(i) => self.stack.truncate(i + 1),
+            None => self.stack.push(screen),
+        }
+    }
+
+    /// Show the game, with only the menu under it.
+    pub fn play(&mut self) {
+        self.stack = vec![Screen::Menu, Screen::Game];
+    }
+
+    /// Go back to the main menu.
+    pub fn menu(&mut self) {
+        self.stack.truncate(1);
+    }
+
+    /// Go back to the screen under the current one. Returns false on the main menu, which has
+    /// nothing under it.
+    pub fn back(&mut self) -> bool {
+        if self.stack.len() > 1 {
+            self.stack.pop();
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! The player's preferences, kept in `settings.toml` under the user's configuration directory.
+//!
+//! The file is a small subset of TOML: `key = value` lines whose values are quoted strings,
+//! numbers or booleans, `[section]` headers and `#` comments. Unknown keys are ignored and
+//! missing ones keep their defaults, so older and newer versions of the game can share a file.
+//! Values that can't be read keep their defaults too, and the game then leaves the file alone
+//! rather than save over the player's mistake.
+//!
+//! ```text
+//! difficulty = "custom"
+//! first_click = "safe"
+//! question_marks = true
+//! theme = "dark"
+//!
+//! [custom]
+//! width = 40
+//! height = 20
+//! mines = 150
+//!
+//! [window]
+//! width = 1280
+//! height = 720
+//! ```
+//!
+//! Bindings have a section of their own, described in `bindings.rs`.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use bindings::Bindings;
+use game::{FirstClick, Preset};
+use paths;
+
+const FILE_NAME: &'static str = "settings.toml";
+
+/// The widest frames the settings screen offers.
+pub const MAX_FRAME_WIDTH: f64 = 5.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// The board new games are dealt: `difficulty`, with the size of a custom board under
+    /// `[custom]`.
+    pub preset: Preset,
+    /// What the first cell opened may hold.
+    pub first_click: FirstClick,
+    /// Whether flags turn into question marks before coming off.
+    pub question_marks: bool,
+    /// The name of the colour theme.
+    pub theme: String,
+    /// The name of the skin cells are drawn with, or `flat` for plain shapes.
+    pub skin: String,
+    /// What the mouse buttons and keys do, under `[bindings]`.
+    pub bindings: Bindings,
+    /// The width of the frames around the buttons, from 0 to `MAX_FRAME_WIDTH`.
+    pub frame_width: f64,
+    /// The window's size when the game was last closed, under `[window]`. Only the size is kept:
+    /// where the window opens is left to the window manager.
+    pub window: Option<[u32; 2]>,
+    path: Option<PathBuf>,
+    /// Whether `save` may write the file: not when it couldn't be read cleanly.
+    writable: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            preset: Preset::Beginner,
+            first_click: FirstClick::Any,
+            question_marks: false,
+            theme: "classic".to_owned(),
+            skin: "classic".to_owned(),
+            bindings: Default::default(),
+            frame_width: 1.0,
+            window: None,
+            path: None,
+            writable: true,
+        }
+    }
+}
+
+impl Settings {
+    /// The settings file in the user's configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        paths::config_dir().map(|dir| dir.join(FILE_NAME))
+    }
+
+    /// The defaults, for when the settings file can't be read. `save` leaves the file alone, so
+    /// whatever made it unreadable can still be put right by hand.
+    pub fn unsaved() -> Settings {
+        Settings { writable: false, ..Default::default() }
+    }
+
+    /// Load the settings from the default location. A missing file gives the defaults.
+    pub fn load() -> io::Result<(Settings, Vec<String>)> {
+        match Settings::default_path() {
+            Some(path) => Settings::load_from(&path),
+            None => Ok((Default::default(), vec![])),
+        }
+    }
+
+    /// Load the settings as `load` does, telling the player about every value replaced by its
+    /// default, or falling back to defaults that won't be saved if the file can't be read.
+    pub fn load_or_warn() -> Settings {
+        match Settings::load() {
+            Ok((settings, problems)) => {
+                for problem in problems {
+                    println!("Settings: {}, using the default instead", problem);
+                }
+                settings
+            },
+            Err(e) => {
+                println!("Couldn't read settings, using the defaults: {}", e);
+                Settings::unsaved()
+            },
+        }
+    }
+
+    /// Load the settings from `path`, which `save` then writes back to, along with a description
+    /// of every value that had to be replaced by its default. A file with such values is never
+    /// saved over.
+    pub fn load_from(path: &Path) -> io::Result<(Settings, Vec<String>)> {
+        let mut text = String::new();
+        match File::open(path) {
+            Ok(mut file) => {
+                try!(file.read_to_string(&mut text));
+            },
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+        let (mut settings, problems) = Settings::parse(&text);
+        settings.path = Some(path.to_owned());
+        settings.writable = problems.is_empty();
+        Ok((settings, problems))
+    }
+
+    /// Read settings from the text of a settings file. Lines that can't be read and values that
+    /// don't make sense keep their defaults, and are described in the list returned alongside.
+    pub fn parse(text: &str) -> (Settings, Vec<String>) {
+        let mut settings = Settings::default();
+        let mut difficulty = settings.preset.name().to_owned();
+        let (mut width, mut height, mut depth, mut mines) = (9, 9, 1, 10);
+        let (mut window_width, mut window_height) = (None, None);
+        let (pairs, mut problems) = parse_pairs(text);
+        for (key, value) in pairs {
+            let read = match key.as_str() {
+                "difficulty" => value.string(&key).map(|value| difficulty = value),
+                "custom.width" => value.count(&key).map(|value| width = value),
+                "custom.height" => value.count(&key).map(|value| height = value),
+                "custom.depth" => value.count(&key).map(|value| depth = value),
+                "custom.mines" => value.count(&key).map(|value| mines = value),
+                "first_click" => value.string(&key).and_then(|name| {
+                    FirstClick::from_name(&name).map(|policy| settings.first_click = policy)
+                        .ok_or(format!("unknown first click policy: {}", name))
+                }),
+                "question_marks" => value.boolean(&key).map(|value| {
+                    settings.question_marks = value
+                }),
+                "frame_width" => value.float(&key).and_then(|value| {
+                    if value >= 0.0 && value <= MAX_FRAME_WIDTH {
+                        settings.frame_width = value;
+                        Ok(())
+                    } else {
+                        Err(format!("{} should be from 0 to {}", key, MAX_FRAME_WIDTH))
+                    }
+                }),
+                "window.width" => value.size(&key).map(|value| window_width = Some(value)),
+                "window.height" => value.size(&key).map(|value| window_height = Some(value)),
+                "theme" => value.string(&key).map(|value| settings.theme = value),
+                "skin" => value.string(&key).map(|value| settings.skin = value),
+                "bindings.preset" => value.string(&key).and_then(|name| {
+                    settings.bindings.set_preset(&name)
+                }),
+                _ if key.starts_with("bindings.") => value.string(&key).and_then(|action| {
+                    settings.bindings.set_by_name(&key["bindings.".len()..], &action)
+                }),
+                _ => Ok(()),
+            };
+            if let Err(e) = read {
+                problems.push(e);
+            }
+        }
+        match Settings::preset(&difficulty, width, height, depth, mines) {
+            Ok(preset) => settings.preset = preset,
+            Err(e) => problems.push(e),
+        }
+        if let (Some(width), Some(height)) = (window_width, window_height) {
+            settings.window = Some([width, height]);
+        }
+        (settings, problems)
+    }
+
+    /// The preset named `difficulty`, which may be a standard one or `custom`.
+    fn preset(difficulty: &str, width: usize, height: usize, depth: usize,
+              mines: usize) -> Result<Preset, String> {
+        if difficulty != "custom" {
+            return Preset::standard(difficulty)
+                .ok_or(format!("unknown difficulty: {}", difficulty));
+        }
+        let preset = Preset::Custom { width: width, height: height, depth: depth, mines: mines };
+        try!(preset.check().map_err(|e| format!("the custom board won't do: {}", e)));
+        Ok(preset)
+    }
+
+    /// Write the settings back to where they were loaded from, or to the default location.
+    /// Settings that weren't read cleanly are never written over the file they came from.
+    pub fn save(&self) -> io::Result<()> {
+        if !self.writable {
+            return Err(io::Error::new(io::ErrorKind::Other,
+                                      "the settings file had problems, so it is left as it is"));
+        }
+        let path = match self.path.clone().or_else(Settings::default_path) {
+            Some(path) => path,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound,
+                                              "no configuration directory")),
+        };
+        if let Some(dir) = path.parent() {
+            try!(fs::create_dir_all(dir));
+        }
+        let mut file = try!(File::create(&path));
+        file.write_all(self.to_text().as_bytes())
+    }
+
+    /// The settings file's text.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# Minesweeper settings\n\n");
+        let difficulty = self.preset.standard_name();
+        text.push_str(&format!("difficulty = {}\n", quote(difficulty)));
+        text.push_str(&format!("first_click = {}\n", quote(self.first_click.name())));
+        text.push_str(&format!("question_marks = {}\n", self.question_marks));
+        text.push_str(&format!("theme = {}\nskin = {}\n", quote(&self.theme), quote(&self.skin)));
+        text.push_str(&format!("frame_width = {:?}\n", self.frame_width));
+        match self.preset {
+            Preset::Custom { width, height, depth, mines } if difficulty == "custom" => {
+                text.push_str(&format!("\n[custom]\nwidth = {}\nheight = {}\ndepth = {}\n\
+                                        mines = {}\n", width, height, depth, mines));
+            },
+            _ => (),
+        }
+        if let Some(window) = self.window {
+            text.push_str(&format!("\n[window]\nwidth = {}\nheight = {}\n", window[0], window[1]));
+        }
+        text.push_str(&format!("\n[bindings]\npreset = {}\n", quote(&self.bindings.preset)));
+        for (trigger, action) in self.bindings.overrides() {
+            text.push_str(&format!("{} = {}\n", trigger, quote(action)));
+        }
+        text
+    }
+}
+
+/// A value on the right of a `key = value` line.
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+impl Value {
+    fn string(&self, key: &str) -> Result<String, String> {
+        match *self {
+            Value::String(ref s) => Ok(s.clone()),
+            _ => Err(format!("{} should be a quoted string", key)),
+        }
+    }
+
+    /// A whole number of at least 0.
+    fn count(&self, key: &str) -> Result<usize, String> {
+        match *self {
+            Value::Integer(n) if n >= 0 => Ok(n as usize),
+            _ => Err(format!("{} should be a whole number of at least 0", key)),
+        }
+    }
+
+    /// A whole number that fits a window dimension.
+    fn size(&self, key: &str) -> Result<u32, String> {
+        match *self {
+            Value::Integer(n) if n >= 0 && n <= u32::max_value() as i64 => Ok(n as u32),
+            _ => Err(format!("{} should be a whole number of pixels", key)),
+        }
+    }
+
+    fn float(&self, key: &str) -> Result<f64, String> {
+        match *self {
+            Value::Integer(n) => Ok(n as f64),
+            Value::Float(x) => Ok(x),
+            _ => Err(format!("{} should be a number", key)),
+        }
+    }
+
+    fn boolean(&self, key: &str) -> Result<bool, String> {
+        match *self {
+            Value::Boolean(b) => Ok(b),
+            _ => Err(format!("{} should be true or false", key)),
+        }
+    }
+}
+
+/// Every `key = value` line, with keys under a `[section]` header prefixed by `section.`, and a
+/// description of every line that isn't one.
+fn parse_pairs(text: &str) -> (Vec<(String, Value)>, Vec<String>) {
+    let mut pairs = vec![];
+    let mut problems = vec![];
+    let mut section = String::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let malformed = format!("line {}: expected `key = value`: {}", number + 1, line);
+        if line.starts_with('[') {
+            if line.ends_with(']') {
+                section = format!("{}.", line[1..line.len() - 1].trim());
+            } else {
+                problems.push(malformed);
+            }
+            continue;
+        }
+        let pair = line.find('=').and_then(|equals| {
+            let key = line[..equals].trim();
+            parse_value(line[equals + 1..].trim())
+                .and_then(|value| if key.is_empty() { None } else { Some((key, value)) })
+        });
+        match pair {
+            Some((key, value)) => pairs.push((format!("{}{}", section, key), value)),
+            None => problems.push(malformed),
+        }
+    }
+    (pairs, problems)
+}
+
+fn parse_value(text: &str) -> Option<Value> {
+    if text.starts_with('"') {
+        // A quoted string, perhaps followed by a comment.
+        let mut value = String::new();
+        let mut chars = text[1..].chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    let rest = chars.as_str().trim();
+                    return if rest.is_empty() || rest.starts_with('#') {
+                        Some(Value::String(value))
+                    } else {
+                        None
+                    };
+                },
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ '"') | Some(c @ '\\') => value.push(c),
+                    _ => return None,
+                },
+                c => value.push(c),
+            }
+        }
+        return None;
+    }
+    let text = text.split('#').next().unwrap_or("").trim();
+    match text {
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        _ => text.parse::<i64>().map(Value::Integer)
+            .or_else(|_| text.parse::<f64>().map(Value::Float))
+            .ok(),
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+                          .replace('\t', "\\t"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut settings = Settings::default();
+        settings.preset = Preset::Custom { width: 40, height: 20, depth: 1, mines: 150 };
+        settings.question_marks = true;
+        settings.theme = "dark \"night\"".to_owned();
+        settings.window = Some([1280, 720]);
+        assert_eq!(Settings::parse(&settings.to_text()), (settings, vec![]));
+    }
+
+    #[test]
+    fn bad_values_keep_their_defaults() {
+        let text = "theme = \"dark\"\nquestion_marks = 3\nfirst_click = \"lucky\"\nnonsense\n\
+                    frame_width = nan\nskin = \"flat\"\n\
+                    [custom]\nwidth = 4\nheight = 4\nmines = 16\n";
+        let (settings, problems) = Settings::parse(&format!("difficulty = \"custom\"\n{}", text));
+        assert_eq!(problems.len(), 5);
+        assert_eq!(settings.frame_width, 1.0);
+        assert_eq!(settings.theme, "dark");
+        assert_eq!(settings.skin, "flat");
+        assert_eq!(settings.question_marks, false);
+        assert_eq!(settings.first_click, FirstClick::Any);
+        assert_eq!(settings.preset, Preset::Beginner);
+    }
+}
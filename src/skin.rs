@@ -0,0 +1,228 @@
+//! Skins: pictures of cells cut from a single sprite sheet.
+//!
+//! A skin is a directory holding `sheet.png` and a manifest, `skin.txt`, which names the
+//! rectangle of the sheet each sprite takes, one per line:
+//!
+//! ```text
+//! # name left top width height, in pixels
+//! unopened 0 0 32 32
+//! 1 96 0 32 32
+//! ```
+//!
+//! Every sprite must be given: `unopened`, `pressed`, the numbers `0` to `8`, `flag`,
+//! `question`, `mine`, `exploded` and `wrong-flag`. The game comes with `classic`, built in,
+//! and finds others under `skins` in the assets directory (see `assets`).
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use assets;
+use board::Tile;
+use map::{Cell, Interaction};
+
+pub const MANIFEST: &'static str = "skin.txt";
+pub const SHEET: &'static str = "sheet.png";
+
+/// A picture of a cell.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sprite {
+    Unopened,
+    /// An unopened cell while a button is held over it.
+    Pressed,
+    /// An opened cell with up to 8 mines around it.
+    Number(usize),
+    Flag,
+    Question,
+    Mine,
+    /// The mine that went off.
+    Exploded,
+    /// A flag on a cell without a mine, shown once the game is lost.
+    WrongFlag,
+}
+
+impl Sprite {
+    /// Every sprite, in the order of the classic sheet.
+    pub fn all() -> Vec<Sprite> {
+        let mut all = vec![Sprite::Unopened, Sprite::Pressed];
+        all.extend((0..9).map(Sprite::Number));
+        all.extend_from_slice(&[Sprite::Flag, Sprite::Question, Sprite::Mine, Sprite::Exploded,
+                                Sprite::WrongFlag]);
+        all
+    }
+
+    /// The name the manifest gives the sprite.
+    pub fn name(&self) -> String {
+        match *self {
+            Sprite::Unopened => "unopened".to_owned(),
+            Sprite::Pressed => "pressed".to_owned(),
+            Sprite::Number(n) => n.to_string(),
+            Sprite::Flag => "flag".to_owned(),
+            Sprite::Question => "question".to_owned(),
+            Sprite::Mine => "mine".to_owned(),
+            Sprite::Exploded => "exploded".to_owned(),
+            Sprite::WrongFlag => "wrong-flag".to_owned(),
+        }
+    }
+
+    /// Where the sprite is in `all`. Numbers above 8, which have no sprite, take the place of
+    /// `Unopened`.
+    pub fn index(&self) -> usize {
+        match *self {
+            Sprite::Unopened => 0,
+            Sprite::Pressed => 1,
+            Sprite::Number(n) if n <= 8 => 2 + n,
+            Sprite::Number(_) => 0,
+            Sprite::Flag => 11,
+            Sprite::Question => 12,
+            Sprite::Mine => 13,
+            Sprite::Exploded => 14,
+            Sprite::WrongFlag => 15,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Sprite> {
+        Sprite::all().into_iter().find(|sprite| sprite.name() == name)
+    }
+
+    /// The sprite a tile is drawn with. Numbers above 8, which only layered boards have, have
+    /// none.
+    pub fn for_tile(tile: Tile) -> Option<Sprite> {
+        match tile {
+            Tile::Unknown => Some(Sprite::Unopened),
+            Tile::Flagged => Some(Sprite::Flag),
+            Tile::Questioned => Some(Sprite::Question),
+            Tile::Number(n) if n <= 8 => Some(Sprite::Number(n)),
+            Tile::Number(_) => None,
+            Tile::Mine => Some(Sprite::Mine),
+        }
+    }
+
+    /// The sprite a tile of a lost game is drawn with, given the `cell` behind it: an opened mine
+    /// is one that went off, and a flag on a cell without a mine was wrong.
+    pub fn for_lost_tile(tile: Tile, cell: &Cell) -> Option<Sprite> {
+        match tile {
+            Tile::Mine if cell.interaction() == Interaction::Opened => Some(Sprite::Exploded),
+            Tile::Flagged if !cell.is_mine() => Some(Sprite::WrongFlag),
+            _ => Sprite::for_tile(tile),
+        }
+    }
+}
+
+/// A skin's manifest: where each sprite is on its sheet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Skin {
+    pub name: String,
+    pub sheet: Sheet,
+    /// The rectangle of each sprite, as `[left, top, width, height]`, in the order of
+    /// `Sprite::all`.
+    rects: Vec<[u32; 4]>,
+}
+
+/// Where a skin's sprite sheet comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sheet {
+    /// A PNG built into the game.
+    Bundled(&'static [u8]),
+    File(PathBuf),
+}
+
+impl Skin {
+    /// The skin named `name`: the one in the assets directory if it has it, or else the one
+    /// built in.
+    pub fn named(name: &str) -> io::Result<Skin> {
+        match assets::find(&format!("{}/{}", assets::SKINS_PATH, name)) {
+            Some(dir) => Skin::load(&dir),
+            None => Skin::bundled(name).unwrap_or_else(|| {
+                Err(io::Error::new(io::ErrorKind::NotFound, format!("no skin named {}", name)))
+            }),
+        }
+    }
+
+    /// The built-in skin named `name`, if there is one.
+    pub fn bundled(name: &str) -> Option<io::Result<Skin>> {
+        if name != assets::CLASSIC {
+            return None;
+        }
+        Some(Skin::parse(name, assets::CLASSIC_MANIFEST)
+            .map(|skin| Skin { sheet: Sheet::Bundled(assets::CLASSIC_SHEET), ..skin })
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+
+    /// Load the skin in `dir`, named after the directory.
+    pub fn load(dir: &Path) -> io::Result<Skin> {
+        let mut text = String::new();
+        let manifest = dir.join(MANIFEST);
+        try!(File::open(&manifest).and_then(|mut file| file.read_to_string(&mut text))
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", manifest.display(), e))));
+        let name = dir.file_name()
+            .map_or(String::new(), |name| name.to_string_lossy().into_owned());
+        let mut skin = try!(Skin::parse(&name, &text).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", manifest.display(), e))
+        }));
+        skin.sheet = Sheet::File(dir.join(SHEET));
+        Ok(skin)
+    }
+
+    /// Read a manifest. The sheet is taken to be `sheet.png` in the working directory.
+    pub fn parse(name: &str, text: &str) -> Result<Skin, String> {
+        let all = Sprite::all();
+        let mut rects: Vec<Option<[u32; 4]>> = vec![None; all.len()];
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let malformed = || {
+                format!("line {}: expected `name left top width height`: {}", number + 1, line)
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() != 5 {
+                return Err(malformed());
+            }
+            let sprite = try!(Sprite::from_name(words[0])
+                .ok_or(format!("line {}: unknown sprite: {}", number + 1, words[0])));
+            let mut rect = [0; 4];
+            for (value, word) in rect.iter_mut().zip(&words[1..]) {
+                *value = try!(word.parse::<u32>().map_err(|_| malformed()));
+            }
+            if rect[2] == 0 || rect[3] == 0 {
+                return Err(format!("line {}: {} is empty", number + 1, words[0]));
+            }
+            rects[sprite.index()] = Some(rect);
+        }
+        let missing: Vec<String> = all.iter().zip(&rects)
+            .filter(|&(_, rect)| rect.is_none())
+            .map(|(sprite, _)| sprite.name())
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("missing sprites: {}", missing.join(", ")));
+        }
+        Ok(Skin {
+            name: name.to_owned(),
+            sheet: Sheet::File(PathBuf::from(SHEET)),
+            rects: rects.into_iter().map(Option::unwrap).collect(),
+        })
+    }
+
+    /// Where `sprite` is on the sheet, as `[left, top, width, height]` in pixels.
+    pub fn rect(&self, sprite: Sprite) -> [u32; 4] {
+        self.rects[sprite.index()]
+    }
+}
+
+/// The names of the built-in skin and those in the assets directory, sorted.
+pub fn available() -> Vec<String> {
+    let dir = assets::dir().map(|dir| dir.join(assets::SKINS_PATH));
+    let mut names: Vec<String> = dir.into_iter()
+        .flat_map(|dir| fs::read_dir(dir).into_iter())
+        .flat_map(|entries| entries)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(MANIFEST).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.push(assets::CLASSIC.to_owned());
+    names.sort();
+    names.dedup();
+    names
+}
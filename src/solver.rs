@@ -0,0 +1,231 @@
+//! Working out cells from the numbers showing, as a player does without guessing.
+//!
+//! `deduce` looks at a board as the player sees it. Each opened number says how many of its
+//! closed neighbours hold mines, less the flags around it. A number with no mines left to find
+//! makes its closed neighbours safe, and one with as many left as it has closed neighbours makes
+//! them all mines. Where the closed neighbours of one number are among those of another, the
+//! cells only the second sees hold the difference, which settles them in the same way. The
+//! mine counter does the same for the whole board.
+//!
+//! `analyse` plays a board from one cell on, opening and flagging only what `deduce` finds, to
+//! tell whether it can be cleared without a guess.
+
+use std::fmt;
+
+use board::{Board, Tile};
+use coord::Coord;
+use map::{Interaction, Map};
+
+/// What is known about a closed cell.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Deduction {
+    Safe(Coord),
+    Mine(Coord),
+}
+
+/// Closed cells holding a known number of mines between them.
+struct Constraint {
+    /// Indices of the cells, sorted.
+    cells: Vec<usize>,
+    mines: usize,
+}
+
+/// The closed cells whose contents follow from the numbers showing, each listed once. Flags are
+/// taken to be right.
+pub fn deduce(board: &Board) -> Vec<Deduction> {
+    let size = board.size();
+    let closed = |coord: Coord| {
+        match board.tile(coord) {
+            Tile::Unknown | Tile::Questioned => true,
+            _ => false,
+        }
+    };
+
+    let mut constraints = vec![];
+    for coord in board.coords() {
+        let n = match board.tile(coord) {
+            Tile::Number(n) => n,
+            _ => continue,
+        };
+        let mut cells: Vec<usize> = board.neighbours(coord)
+            .filter(|&neighbour| closed(neighbour))
+            .map(|neighbour| size.index(neighbour))
+            .collect();
+        if cells.is_empty() {
+            continue;
+        }
+        cells.sort();
+        let flags = board.neighbours(coord)
+            .filter(|&neighbour| board.tile(neighbour) == Tile::Flagged)
+            .count();
+        // Wrong flags can leave a number over-satisfied, which tells nothing.
+        if flags <= n && n - flags <= cells.len() {
+            constraints.push(Constraint { cells: cells, mines: n - flags });
+        }
+    }
+    let all: Vec<usize> = board.coords().filter(|&coord| closed(coord))
+        .map(|coord| size.index(coord))
+        .collect();
+    if board.mines_left() >= 0 && board.mines_left() as usize <= all.len() {
+        constraints.push(Constraint { cells: all, mines: board.mines_left() as usize });
+    }
+
+    // `None` for cells nothing is known about.
+    let mut known: Vec<Option<bool>> = vec![None; size.len()];
+    {
+        let mut settle = |cells: &[usize], mines: usize| {
+            if mines == 0 || mines == cells.len() {
+                for &cell in cells {
+                    known[cell] = Some(mines > 0);
+                }
+            }
+        };
+        for a in constraints.iter() {
+            settle(&a.cells, a.mines);
+            for b in constraints.iter() {
+                if a.cells.len() < b.cells.len() && b.mines >= a.mines &&
+                   is_subset(&a.cells, &b.cells) {
+                    let rest: Vec<usize> = b.cells.iter().cloned()
+                        .filter(|cell| a.cells.binary_search(cell).is_err())
+                        .collect();
+                    settle(&rest, b.mines - a.mines);
+                }
+            }
+        }
+    }
+
+    known.iter().enumerate().filter_map(|(index, &known)| {
+        known.map(|mine| {
+            let coord = size.coord(index);
+            if mine { Deduction::Mine(coord) } else { Deduction::Safe(coord) }
+        })
+    }).collect()
+}
+
+/// Whether every element of sorted `a` is in sorted `b`.
+fn is_subset(a: &[usize], b: &[usize]) -> bool {
+    a.iter().all(|cell| b.binary_search(cell).is_ok())
+}
+
+/// How far a board can be played without guessing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Analysis {
+    /// The cell opened first.
+    pub start: Coord,
+    pub mines: usize,
+    /// The cells without a mine, all of which clearing the board opens.
+    pub safe: usize,
+    /// The cells opened before running out of deductions.
+    pub opened: usize,
+    /// The board's 3BV.
+    pub three_bv: usize,
+}
+
+impl Analysis {
+    /// Whether the whole board was cleared.
+    pub fn is_cleared(&self) -> bool {
+        self.opened == self.safe
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(writeln!(f, "start: {} {} {}", self.start.column + 1, self.start.row + 1,
+                      self.start.layer + 1));
+        try!(writeln!(f, "mines: {}", self.mines));
+        try!(writeln!(f, "3BV: {}", self.three_bv));
+        try!(writeln!(f, "opened: {}/{}", self.opened, self.safe));
+        if self.is_cleared() {
+            writeln!(f, "cleared without guessing")
+        } else {
+            writeln!(f, "needs a guess")
+        }
+    }
+}
+
+/// The cell a player would most like to start on: the first without a mine or a number around
+/// it, or failing that the first without a mine.
+pub fn default_start(map: &Map) -> Option<Coord> {
+    map.cells().find(|&(_, cell)| !cell.is_mine() && cell.nearby() == 0)
+        .or_else(|| map.cells().find(|&(_, cell)| !cell.is_mine()))
+        .map(|(coord, _)| coord)
+}
+
+/// Open `start` on a copy of `map`'s mines and play on from it for as long as `deduce` finds
+/// something. Starting on a mine opens nothing.
+pub fn analyse(map: &Map, start: Coord) -> Analysis {
+    let mines: Vec<Coord> = map.cells().filter(|&(_, cell)| cell.is_mine())
+        .map(|(coord, _)| coord)
+        .collect();
+    let mut play = Map::with_mines(map.width, map.height, map.depth, map.topology, &mines);
+    let safe = map.size().len() - mines.len();
+
+    if !play.open(start) {
+        loop {
+            let deductions = deduce(&Board::from_map(&play));
+            if deductions.is_empty() {
+                break;
+            }
+            for deduction in deductions {
+                match deduction {
+                    Deduction::Safe(coord) => {
+                        play.open(coord);
+                    },
+                    Deduction::Mine(coord) => play.toggle_flag(coord),
+                }
+            }
+        }
+    }
+
+    let opened = play.cells()
+        .filter(|&(_, cell)| cell.interaction() == Interaction::Opened && !cell.is_mine())
+        .count();
+    Analysis {
+        start: start,
+        mines: mines.len(),
+        safe: safe,
+        opened: opened,
+        three_bv: map.three_bv(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyse, deduce, Deduction};
+    use board::Board;
+    use coord::Coord;
+    use minefield;
+
+    #[test]
+    fn subset_rule() {
+        // The 1-2-1 along the bottom: no number settles anything alone, but the 1s' cells are
+        // among the 2's, which leaves a mine in each corner.
+        let mut map = minefield::parse("*.*\n...\n").unwrap();
+        for column in 0..3 {
+            map.open(Coord::new(column, 1, 0));
+        }
+        let deductions = deduce(&Board::from_map(&map));
+        assert!(deductions.contains(&Deduction::Mine(Coord::new(0, 0, 0))));
+        assert!(deductions.contains(&Deduction::Mine(Coord::new(2, 0, 0))));
+        for deduction in deductions {
+            match deduction {
+                Deduction::Mine(coord) => assert!(map.is_mine(coord)),
+                Deduction::Safe(coord) => assert!(!map.is_mine(coord)),
+            }
+        }
+    }
+
+    #[test]
+    fn no_guess_board() {
+        let map = minefield::parse("*.*\n...\n...\n").unwrap();
+        let analysis = analyse(&map, Coord::new(1, 2, 0));
+        assert!(analysis.is_cleared());
+        assert_eq!((analysis.mines, analysis.safe, analysis.opened), (2, 7, 7));
+    }
+
+    #[test]
+    fn fifty_fifty() {
+        let map = minefield::parse("*.\n..\n").unwrap();
+        assert!(!analyse(&map, Coord::new(1, 1, 0)).is_cleared());
+    }
+}
@@ -0,0 +1,143 @@
+//! Drawing square cells with a skin's sprites.
+//!
+//! Conrod's primitives are flat shapes and text, so skinned cells are drawn with piston's `Image`
+//! straight onto the window, after the `Ui`. The `BoardView` still lays the board out and reads
+//! the mouse; it only leaves out the cells drawn here (see `BoardView::skinned`). Hexagons and
+//! triangles, and the numbers above 8 of layered boards, have no sprites and stay flat.
+
+use std::io::{self, Write};
+
+use conrod::{Dimensions, Rect, Scalar};
+use image;
+use piston_window::{Context, Flip, G2d, Graphics, Image, PistonWindow, Texture,
+                    TextureSettings};
+
+use minesweeper::assets;
+use minesweeper::board::Board;
+use minesweeper::coord::Coord;
+use minesweeper::map::Map;
+use minesweeper::skin::{Sheet, Skin, Sprite};
+use minesweeper::topology::Tiling;
+
+use board_view::{Layout, Viewport};
+
+/// The setting that turns skins off.
+pub const FLAT: &'static str = "flat";
+
+type SheetTexture = <G2d<'static> as Graphics>::Texture;
+
+/// A skin and its sheet, loaded onto the graphics card.
+pub struct Sprites {
+    pub skin: Skin,
+    texture: SheetTexture,
+}
+
+impl Sprites {
+    /// Load the skin named `name`. One in the assets directory that fails to load is reported,
+    /// and the built-in skin of that name used instead; if there's none, the cells are drawn
+    /// flat.
+    pub fn load(window: &mut PistonWindow, name: &str) -> Option<Sprites> {
+        let e = match Skin::named(name).map_err(|e| e.to_string())
+            .and_then(|skin| Sprites::new(window, skin)) {
+            Ok(sprites) => return Some(sprites),
+            Err(e) => e,
+        };
+        let _ = writeln!(io::stderr(), "Couldn't load the {} skin: {}", name, e);
+        if assets::dir().is_none() {
+            return None;
+        }
+        match Skin::bundled(name) {
+            Some(skin) => {
+                let sprites = skin.map_err(|e| e.to_string())
+                    .and_then(|skin| Sprites::new(window, skin));
+                if sprites.is_ok() {
+                    let _ = writeln!(io::stderr(), "Using the built-in {} skin instead", name);
+                }
+                sprites.ok()
+            },
+            None => None,
+        }
+    }
+
+    /// Put `skin`'s sheet on the graphics card.
+    fn new(window: &mut PistonWindow, skin: Skin) -> Result<Sprites, String> {
+        let settings = TextureSettings::new();
+        let texture = try!(match skin.sheet {
+            Sheet::File(ref path) => {
+                Texture::from_path(&mut window.factory, path, Flip::None, &settings)
+                    .map_err(|e| format!("{}: {}", path.display(), e))
+            },
+            Sheet::Bundled(png) => {
+                let sheet = try!(image::load_from_memory(png).map_err(|e| e.to_string()));
+                Texture::from_image(&mut window.factory, &sheet.to_rgba(), &settings)
+                    .map_err(|e| format!("{:?}", e))
+            },
+        });
+        Ok(Sprites {
+            skin: skin,
+            texture: texture,
+        })
+    }
+
+    /// Whether the board is drawn with sprites at all.
+    pub fn covers(board: &Board) -> bool {
+        board.topology.tiling == Tiling::Square
+    }
+
+    /// Draw the cells of `layer` that have sprites, laid out as a `BoardView` in `rect` with
+    /// `viewport` lays them out, in a window of `window` pixels. Cells on the edge of `rect` are
+    /// cut to it, as the view crops its own. The `pressed` cells, from `pressed_cells`, are drawn
+    /// pressed. Given the map behind a lost game, `lost`, the mines that went off and the flags
+    /// on cells without one are drawn as such.
+    pub fn draw(&self, c: Context, g: &mut G2d, window: Dimensions, board: &Board, layer: usize,
+                rect: Rect, viewport: Viewport, pressed: &[Coord], lost: Option<&Map>) {
+        if !Sprites::covers(board) {
+            return;
+        }
+        let layout = Layout::new(board, rect, viewport);
+        let cell = layout.cell_size();
+        let (columns, rows) = layout.visible(rect);
+        for row in rows {
+            for column in columns.clone() {
+                let coord = Coord::new(column, row, layer);
+                let tile = board.tile(coord);
+                let sprite = match lost {
+                    Some(map) => Sprite::for_lost_tile(tile, map.cell(coord)),
+                    None => Sprite::for_tile(tile),
+                };
+                let sprite = match sprite {
+                    Some(_) if pressed.contains(&coord) => Sprite::Pressed,
+                    Some(sprite) => sprite,
+                    None => continue,
+                };
+                let centre = layout.centre(column, row);
+                let (left, top) = (centre[0] - cell[0] / 2.0, centre[1] + cell[1] / 2.0);
+
+                // The part of the cell inside `rect`, as fractions of the cell.
+                let x0 = ((rect.left() - left) / cell[0]).max(0.0);
+                let x1 = ((rect.right() - left) / cell[0]).min(1.0);
+                let y0 = ((top - rect.top()) / cell[1]).max(0.0);
+                let y1 = ((top - rect.bottom()) / cell[1]).min(1.0);
+                if x0 >= x1 || y0 >= y1 {
+                    continue;
+                }
+
+                let source = self.skin.rect(sprite);
+                let (w, h) = (source[2] as Scalar, source[3] as Scalar);
+                let source = [source[0] as i32 + (x0 * w) as i32,
+                              source[1] as i32 + (y0 * h) as i32,
+                              ((x1 - x0) * w).round() as i32,
+                              ((y1 - y0) * h).round() as i32];
+
+                // Conrod measures from the middle of the window upwards, piston from the top
+                // left corner downwards.
+                let x = window[0] / 2.0 + left + x0 * cell[0];
+                let y = window[1] / 2.0 - top + y0 * cell[1];
+                Image::new()
+                    .src_rect(source)
+                    .rect([x, y, (x1 - x0) * cell[0], (y1 - y0) * cell[1]])
+                    .draw(&self.texture, &c.draw_state, c.transform, g);
+            }
+        }
+    }
+}
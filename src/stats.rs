@@ -0,0 +1,421 @@
+//! A record of every finished game, kept in a tab separated file under the user's data
+//! directory, and the summaries shown on the statistics screen and by `MineSweeper stats`.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use game::{self, Game, Status};
+use paths;
+
+const FILE_NAME: &'static str = "stats.tsv";
+const HEADER: &'static str = "# finished\tpreset\twidth\theight\tmines\tseed\toutcome\t\
+                               time_ms\tclicks\t3bv\ttopology\tdepth";
+
+/// Number of bars in the time distribution chart.
+pub const DISTRIBUTION_BUCKETS: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Won,
+    Lost,
+}
+
+impl Outcome {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Outcome::Won => "won",
+            Outcome::Lost => "lost",
+        }
+    }
+}
+
+/// One finished game.
+#[derive(Clone, Debug)]
+pub struct Record {
+    /// Seconds since the Unix epoch.
+    pub finished: u64,
+    pub preset: String,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub time_ms: u64,
+    pub clicks: usize,
+    pub three_bv: usize,
+    pub topology: String,
+    pub depth: usize,
+}
+
+impl Record {
+    /// Describe a game that has just ended. Returns `None` while it is still being played.
+    pub fn from_game(game: &Game) -> Option<Record> {
+        let outcome = match game.status {
+            Status::Playing => return None,
+            Status::Failed => Outcome::Lost,
+            Status::Finished => Outcome::Won,
+        };
+        let (width, height, mines) = game.preset.dimensions();
+        let finished = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Some(Record {
+            finished: finished,
+            preset: game.preset.name().to_owned(),
+            width: width,
+            height: height,
+            mines: mines,
+            seed: game.seed,
+            outcome: outcome,
+            time_ms: game::millis(game.elapsed()),
+            clicks: game.clicks,
+            three_bv: game.map.three_bv(),
+            topology: game.topology.name(),
+            depth: game.preset.depth(),
+        })
+    }
+
+    /// What best times are kept apart by: the preset, or for custom boards their size and
+    /// mines, plus the topology unless it's square and the number of layers unless there's just
+    /// one.
+    pub fn category(&self) -> String {
+        let preset = if self.preset == "custom" {
+            format!("custom {}x{}, {} mines", self.width, self.height, self.mines)
+        } else {
+            self.preset.clone()
+        };
+        let mut details = vec![];
+        if self.topology != "square" {
+            details.push(self.topology.clone());
+        }
+        if self.depth > 1 {
+            details.push(format!("{} layers", self.depth));
+        }
+        if details.is_empty() {
+            preset
+        } else {
+            format!("{} ({})", preset, details.join(", "))
+        }
+    }
+
+    fn to_line(&self) -> String {
+        format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                self.finished, self.preset, self.width, self.height, self.mines, self.seed,
+                self.outcome.name(), self.time_ms, self.clicks, self.three_bv, self.topology,
+                self.depth)
+    }
+
+    /// Records written before topologies existed have ten fields and are square, those written
+    /// before layers existed have eleven and are flat.
+    fn from_line(line: &str) -> Option<Record> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 10 || fields.len() > 12 {
+            return None;
+        }
+        let outcome = match fields[6] {
+            "won" => Outcome::Won,
+            "lost" => Outcome::Lost,
+            _ => return None,
+        };
+        Some(Record {
+            finished: match fields[0].parse() { Ok(v) => v, Err(_) => return None },
+            preset: fields[1].to_owned(),
+            width: match fields[2].parse() { Ok(v) => v, Err(_) => return None },
+            height: match fields[3].parse() { Ok(v) => v, Err(_) => return None },
+            mines: match fields[4].parse() { Ok(v) => v, Err(_) => return None },
+            seed: match fields[5].parse() { Ok(v) => v, Err(_) => return None },
+            outcome: outcome,
+            time_ms: match fields[7].parse() { Ok(v) => v, Err(_) => return None },
+            clicks: match fields[8].parse() { Ok(v) => v, Err(_) => return None },
+            three_bv: match fields[9].parse() { Ok(v) => v, Err(_) => return None },
+            topology: fields.get(10).unwrap_or(&"square").to_string(),
+            depth: match fields.get(11).unwrap_or(&"1").parse() {
+                Ok(v) => v,
+                Err(_) => return None,
+            },
+        })
+    }
+}
+
+/// What the statistics screen shows for a set of records.
+#[derive(Clone, Debug, Default)]
+pub struct Summary {
+    pub played: usize,
+    pub won: usize,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    /// Best winning time in milliseconds for each category (see `Record::category`) that has
+    /// been won at least once.
+    pub best_times: Vec<(String, u64)>,
+    /// Winning times bucketed into `DISTRIBUTION_BUCKETS` equal slices of `bucket_ms`.
+    pub distribution: Vec<usize>,
+    pub bucket_ms: u64,
+}
+
+impl Summary {
+    /// Percentage of games won, `0.0` if none were played.
+    pub fn win_rate(&self) -> f64 {
+        if self.played == 0 {
+            0.0
+        } else {
+            self.won as f64 * 100.0 / self.played as f64
+        }
+    }
+}
+
+/// All recorded games, oldest first.
+#[derive(Default)]
+pub struct Stats {
+    pub records: Vec<Record>,
+    path: Option<PathBuf>,
+}
+
+impl Stats {
+    /// The statistics file in the user's data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join(FILE_NAME))
+    }
+
+    /// Load the statistics from the default location. A missing file is an empty history.
+    pub fn load() -> io::Result<Stats> {
+        match Stats::default_path() {
+            Some(path) => Stats::load_from(&path),
+            None => Ok(Default::default()),
+        }
+    }
+
+    /// Load the statistics from `path`. Lines that can't be parsed are skipped.
+    pub fn load_from(path: &Path) -> io::Result<Stats> {
+        let mut records = vec![];
+        match File::open(path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = try!(line);
+                    if line.starts_with('#') || line.trim().is_empty() {
+                        continue;
+                    }
+                    if let Some(record) = Record::from_line(&line) {
+                        records.push(record);
+                    }
+                }
+            },
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+        Ok(Stats {
+            records: records,
+            path: Some(path.to_owned()),
+        })
+    }
+
+    /// Append a record to memory and to the file it was loaded from.
+    pub fn push(&mut self, record: Record) -> io::Result<()> {
+        if let Some(ref path) = self.path {
+            if let Some(dir) = path.parent() {
+                try!(fs::create_dir_all(dir));
+            }
+            let is_new = !path.exists();
+            let mut file = try!(OpenOptions::new().create(true).append(true).open(path));
+            if is_new {
+                try!(writeln!(file, "{}", HEADER));
+            }
+            try!(writeln!(file, "{}", record.to_line()));
+        }
+        self.records.push(record);
+        Ok(())
+    }
+
+    /// The records for one preset, or all of them.
+    pub fn filter(&self, preset: Option<&str>) -> Vec<&Record> {
+        self.records.iter().filter(|record| {
+            preset.map_or(true, |preset| record.preset == preset)
+        }).collect()
+    }
+
+    pub fn summary(&self, preset: Option<&str>) -> Summary {
+        let records = self.filter(preset);
+        let mut summary = Summary::default();
+
+        for record in records.iter() {
+            summary.played += 1;
+            match record.outcome {
+                Outcome::Won => {
+                    summary.won += 1;
+                    summary.current_streak += 1;
+                    if summary.current_streak > summary.longest_streak {
+                        summary.longest_streak = summary.current_streak;
+                    }
+                    let category = record.category();
+                    match summary.best_times.iter().position(|&(ref c, _)| *c == category) {
+                        Some(i) => if record.time_ms < summary.best_times[i].1 {
+                            summary.best_times[i].1 = record.time_ms;
+                        },
+                        None => summary.best_times.push((category, record.time_ms)),
+                    }
+                },
+                Outcome::Lost => summary.current_streak = 0,
+            }
+        }
+
+        let win_times: Vec<u64> = records.iter()
+            .filter(|record| record.outcome == Outcome::Won)
+            .map(|record| record.time_ms)
+            .collect();
+        let slowest = win_times.iter().cloned().max().unwrap_or(0);
+        // Round the bucket up to a whole second so the chart's axis stays readable.
+        summary.bucket_ms = ((slowest / DISTRIBUTION_BUCKETS as u64) / 1000 + 1) * 1000;
+        summary.distribution = vec![0; DISTRIBUTION_BUCKETS];
+        for time in win_times {
+            let bucket = (time / summary.bucket_ms) as usize;
+            summary.distribution[bucket.min(DISTRIBUTION_BUCKETS - 1)] += 1;
+        }
+
+        summary
+    }
+}
+
+pub fn to_csv(records: &[&Record]) -> String {
+    let mut out = "finished,preset,width,height,mines,seed,outcome,time_ms,clicks,3bv,topology,\
+                   depth\n".to_owned();
+    for record in records {
+        out.push_str(&record.to_line().replace('\t', ","));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn to_json(records: &[&Record]) -> String {
+    let entries: Vec<String> = records.iter().map(|record| {
+        format!("  {{\"finished\": {}, \"preset\": {}, \"width\": {}, \"height\": {}, \
+                 \"mines\": {}, \"seed\": {}, \"outcome\": {}, \"time_ms\": {}, \
+                 \"clicks\": {}, \"3bv\": {}, \"topology\": {}, \"depth\": {}}}",
+                record.finished, json_string(&record.preset), record.width, record.height,
+                record.mines, record.seed, json_string(record.outcome.name()), record.time_ms,
+                record.clicks, record.three_bv, json_string(&record.topology), record.depth)
+    }).collect();
+    if entries.is_empty() {
+        "[]\n".to_owned()
+    } else {
+        format!("[\n{}\n]\n", entries.join(",\n"))
+    }
+}
+
+/// `s` as a JSON string, quoted and escaped.
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Format milliseconds as seconds with one decimal, the way times are shown everywhere.
+pub fn format_time(ms: u64) -> String {
+    format!("{}.{}s", ms / 1000, (ms % 1000) / 100)
+}
+
+/// The games played and won and the streaks, a line each.
+pub fn totals_text(summary: &Summary) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("Played:          {}\n", summary.played));
+    out.push_str(&format!("Won:             {} ({:.1}%)\n", summary.won, summary.win_rate()));
+    out.push_str(&format!("Current streak:  {}\n", summary.current_streak));
+    out.push_str(&format!("Longest streak:  {}\n", summary.longest_streak));
+    out
+}
+
+/// The best time of each category, a line each.
+pub fn best_times_text(summary: &Summary) -> String {
+    let mut out = String::new();
+    for &(ref category, time) in summary.best_times.iter() {
+        out.push_str(&format!("Best {:<30} {}\n", format!("{}:", category), format_time(time)));
+    }
+    out
+}
+
+pub fn to_text(summary: &Summary) -> String {
+    let mut out = totals_text(summary);
+    out.push_str(&best_times_text(summary));
+    if summary.won > 0 {
+        out.push_str("Winning times:\n");
+        for (i, &count) in summary.distribution.iter().enumerate() {
+            out.push_str(&format!("  {:>6} {}\n",
+                                  format_time(i as u64 * summary.bucket_ms),
+                                  (0..count).map(|_| "#").collect::<String>()));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_string, Outcome, Record};
+
+    fn record() -> Record {
+        Record {
+            finished: 1467000000,
+            preset: "custom".to_owned(),
+            width: 20,
+            height: 10,
+            mines: 30,
+            seed: 42,
+            outcome: Outcome::Won,
+            time_ms: 61234,
+            clicks: 80,
+            three_bv: 55,
+            topology: "hex-torus".to_owned(),
+            depth: 2,
+        }
+    }
+
+    #[test]
+    fn line_round_trip() {
+        let line = record().to_line();
+        let parsed = Record::from_line(&line).unwrap();
+        assert_eq!(parsed.to_line(), line);
+    }
+
+    #[test]
+    fn older_lines() {
+        let flat = Record::from_line("1467000000\tbeginner\t9\t9\t10\t7\tlost\t500\t3\t12\thex")
+            .unwrap();
+        assert_eq!((flat.topology.as_str(), flat.depth), ("hex", 1));
+        let square = Record::from_line("1467000000\tbeginner\t9\t9\t10\t7\twon\t500\t3\t12")
+            .unwrap();
+        assert_eq!((square.topology.as_str(), square.depth), ("square", 1));
+        assert_eq!(square.outcome, Outcome::Won);
+    }
+
+    #[test]
+    fn bad_lines() {
+        assert!(Record::from_line("").is_none());
+        assert!(Record::from_line("1\tbeginner\t9\t9\t10\t7\tdrawn\t500\t3\t12").is_none());
+        assert!(Record::from_line("1\tbeginner\tnine\t9\t10\t7\twon\t500\t3\t12").is_none());
+        assert!(Record::from_line("1\tbeginner\t9\t9\t10\t7\twon\t500\t3\t12\tsquare\t1\tx")
+            .is_none());
+    }
+
+    #[test]
+    fn categories() {
+        assert_eq!(record().category(), "custom 20x10, 30 mines (hex-torus, 2 layers)");
+        let beginner = Record::from_line("1\tbeginner\t9\t9\t10\t7\twon\t500\t3\t12").unwrap();
+        assert_eq!(beginner.category(), "beginner");
+    }
+
+    #[test]
+    fn json_escapes() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+}
@@ -0,0 +1,140 @@
+//! Colour themes for the GUI.
+//!
+//! A `Palette` colours the window, the buttons and every kind of cell, numbers included. It is
+//! handed to the board through `cell::Style` and to everything else through conrod's `Theme`.
+//! The theme's name is kept in the settings file.
+
+use conrod::{color, Color, Theme};
+
+/// The themes, in the order the settings screen lists them.
+pub const NAMES: [&'static str; 4] = ["classic", "dark", "okabe-ito", "tol"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    pub name: &'static str,
+    pub background: Color,
+    pub button: Color,
+    pub text: Color,
+    pub unopened: Color,
+    pub opened: Color,
+    /// A mine that went off or was shown at the end.
+    pub mine: Color,
+    /// The colours of the numbers 1 to 8. Larger numbers, on layered boards, take `text`.
+    pub numbers: [Color; 8],
+}
+
+impl Palette {
+    pub fn named(name: &str) -> Option<Palette> {
+        let palette = match name {
+            // The colours of the original game.
+            "classic" => Palette {
+                name: "classic",
+                background: color::rgb(0.2, 0.35, 0.45),
+                button: color::rgb(0.8, 0.8, 0.8),
+                text: color::rgb(0.0, 0.0, 0.0),
+                unopened: color::rgb(0.5, 0.5, 0.5),
+                opened: color::rgb(0.8, 0.8, 0.8),
+                mine: color::rgb(0.9, 0.2, 0.2),
+                numbers: [
+                    rgb8(0x00, 0x00, 0xff),
+                    rgb8(0x00, 0x80, 0x00),
+                    rgb8(0xff, 0x00, 0x00),
+                    rgb8(0x00, 0x00, 0x80),
+                    rgb8(0x80, 0x00, 0x00),
+                    rgb8(0x00, 0x80, 0x80),
+                    rgb8(0x00, 0x00, 0x00),
+                    rgb8(0x80, 0x80, 0x80),
+                ],
+            },
+            "dark" => Palette {
+                name: "dark",
+                background: rgb8(0x1e, 0x1e, 0x24),
+                button: rgb8(0x3a, 0x3a, 0x44),
+                text: rgb8(0xe0, 0xe0, 0xe0),
+                unopened: rgb8(0x4a, 0x4a, 0x56),
+                opened: rgb8(0x2a, 0x2a, 0x32),
+                mine: rgb8(0xd0, 0x40, 0x40),
+                numbers: [
+                    rgb8(0x6c, 0x9c, 0xff),
+                    rgb8(0x6c, 0xd0, 0x7a),
+                    rgb8(0xff, 0x70, 0x70),
+                    rgb8(0xc0, 0x8c, 0xff),
+                    rgb8(0xff, 0xb0, 0x50),
+                    rgb8(0x50, 0xd8, 0xd8),
+                    rgb8(0xf0, 0xf0, 0xf0),
+                    rgb8(0xa0, 0xa0, 0xa0),
+                ],
+            },
+            // Okabe and Ito's palette, told apart with any colour vision deficiency. Mines take
+            // its yellow, which no number uses.
+            "okabe-ito" => Palette {
+                name: "okabe-ito",
+                background: rgb8(0x33, 0x33, 0x33),
+                button: rgb8(0xcc, 0xcc, 0xcc),
+                text: rgb8(0x00, 0x00, 0x00),
+                unopened: rgb8(0x99, 0x99, 0x99),
+                opened: rgb8(0xee, 0xee, 0xee),
+                mine: rgb8(0xf0, 0xe4, 0x42),
+                numbers: [
+                    rgb8(0x00, 0x72, 0xb2),
+                    rgb8(0x00, 0x9e, 0x73),
+                    rgb8(0xd5, 0x5e, 0x00),
+                    rgb8(0xcc, 0x79, 0xa7),
+                    rgb8(0xe6, 0x9f, 0x00),
+                    rgb8(0x56, 0xb4, 0xe9),
+                    rgb8(0x00, 0x00, 0x00),
+                    rgb8(0x66, 0x66, 0x66),
+                ],
+            },
+            // Paul Tol's bright scheme, also safe for colour blind players. Mines take its yellow,
+            // and 5 the olive of his muted scheme instead.
+            "tol" => Palette {
+                name: "tol",
+                background: rgb8(0x22, 0x22, 0x33),
+                button: rgb8(0xbb, 0xbb, 0xbb),
+                text: rgb8(0x00, 0x00, 0x00),
+                unopened: rgb8(0x88, 0x88, 0x99),
+                opened: rgb8(0xee, 0xee, 0xee),
+                mine: rgb8(0xcc, 0xbb, 0x44),
+                numbers: [
+                    rgb8(0x44, 0x77, 0xaa),
+                    rgb8(0x22, 0x88, 0x33),
+                    rgb8(0xee, 0x66, 0x77),
+                    rgb8(0xaa, 0x33, 0x77),
+                    rgb8(0x99, 0x99, 0x33),
+                    rgb8(0x66, 0xcc, 0xee),
+                    rgb8(0x00, 0x00, 0x00),
+                    rgb8(0x77, 0x77, 0x77),
+                ],
+            },
+            _ => return None,
+        };
+        Some(palette)
+    }
+
+    /// The colour of an opened number.
+    pub fn number(&self, n: usize) -> Color {
+        if n >= 1 && n <= self.numbers.len() { self.numbers[n - 1] } else { self.text }
+    }
+
+    /// Conrod's theme, which colours the widgets not given colours of their own.
+    pub fn conrod_theme(&self) -> Theme {
+        Theme {
+            name: self.name.to_owned(),
+            background_color: self.background,
+            shape_color: self.button,
+            label_color: self.text,
+            ..Theme::default()
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette::named("classic").unwrap()
+    }
+}
+
+fn rgb8(r: u8, g: u8, b: u8) -> Color {
+    color::rgb_bytes(r, g, b)
+}
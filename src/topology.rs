@@ -0,0 +1,584 @@
+//! How cells are laid out and which cells count as neighbours.
+//!
+//! Which cells a number counts is data, not code: a `Neighbourhood` is a small mask drawn around
+//! the cell, written as rows of `.`, `x` and a single `o` for the cell itself, separated by `/`.
+//! The classic eight neighbours are `xxx/xox/xxx`; knight-move Minesweeper, where numbers count
+//! the mines a knight's move away, is `.x.x./x...x/..o../x...x/.x.x.`. Players can bring their
+//! own mask on a square grid as a `mask:` topology, e.g. `mask:.x./xox/.x.`.
+//!
+//! Hex boards use "odd-r" offset coordinates: cells keep their `(column, row)` indices but every
+//! odd row is drawn shifted half a cell to the right, so a cell touches two cells in the row
+//! above, two in the row below and one on either side. Odd rows therefore use a second mask.
+//!
+//! Triangle boards alternate between triangles pointing up, where the column and row add up to
+//! an even number, and triangles pointing down. Each touches twelve others at its corners, and
+//! the two orientations use mirrored masks.
+//!
+//! Any tiling can also wrap around: on a torus the left edge touches the right edge and the top
+//! touches the bottom, so every cell has its full set of neighbours.
+//!
+//! Boards may be stacked into several layers. A cell then also neighbours the cells directly
+//! above and below it and their neighbours in the layer, so a cube in a square grid has 26.
+//! Layers never wrap.
+
+use std::fmt;
+use std::slice;
+
+use coord::{Coord, Size};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+/// Every direction, clockwise from up. Not every tiling has a neighbour in each: see
+/// `Direction::offset`.
+pub const DIRECTIONS: [Direction; 8] = [
+    Direction::Up,
+    Direction::UpRight,
+    Direction::Right,
+    Direction::DownRight,
+    Direction::Down,
+    Direction::DownLeft,
+    Direction::Left,
+    Direction::UpLeft,
+];
+
+impl Direction {
+    /// Column and row change for a step in this direction from `(column, row)`, or `None` if
+    /// the tiling has no cell that way. Squares step to any of their eight neighbours, hexagons
+    /// to the six sharing an edge, which leaves out straight up and down, and triangles to the
+    /// three sharing an edge: left, right, and down from one pointing up or up from one
+    /// pointing down.
+    pub fn offset(&self, tiling: Tiling, column: usize, row: usize) -> Option<(isize, isize)> {
+        match tiling {
+            Tiling::Square => Some(match *self {
+                Direction::Up => (0, -1),
+                Direction::UpRight => (1, -1),
+                Direction::Right => (1, 0),
+                Direction::DownRight => (1, 1),
+                Direction::Down => (0, 1),
+                Direction::DownLeft => (-1, 1),
+                Direction::Left => (-1, 0),
+                Direction::UpLeft => (-1, -1),
+            }),
+            // Odd rows sit half a cell further right, so their diagonal neighbours do too.
+            Tiling::Hex => {
+                let shift = (row % 2) as isize;
+                match *self {
+                    Direction::UpRight => Some((shift, -1)),
+                    Direction::UpLeft => Some((shift - 1, -1)),
+                    Direction::Right => Some((1, 0)),
+                    Direction::DownRight => Some((shift, 1)),
+                    Direction::DownLeft => Some((shift - 1, 1)),
+                    Direction::Left => Some((-1, 0)),
+                    Direction::Up | Direction::Down => None,
+                }
+            },
+            Tiling::Triangle => {
+                let pointing_up = (column + row) % 2 == 0;
+                match *self {
+                    Direction::Right => Some((1, 0)),
+                    Direction::Left => Some((-1, 0)),
+                    Direction::Down if pointing_up => Some((0, 1)),
+                    Direction::Up if !pointing_up => Some((0, -1)),
+                    _ => None,
+                }
+            },
+        }
+    }
+}
+
+/// The shape of the cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tiling {
+    /// The classic grid, eight neighbours.
+    Square,
+    /// Hexagons, six neighbours.
+    Hex,
+    /// Triangles pointing alternately up and down, twelve neighbours.
+    Triangle,
+}
+
+/// Every cell touching a square at an edge or a corner.
+const SQUARE_MASKS: [&'static str; 1] = ["xxx/xox/xxx"];
+const SQUARE_ADJACENT_MASKS: [&'static str; 1] = [".x./xox/.x."];
+/// Even rows, then odd rows.
+const HEX_MASKS: [&'static str; 2] = ["xx./xox/xx.", ".xx/xox/.xx"];
+/// Triangles pointing up, then triangles pointing down.
+const TRIANGLE_MASKS: [&'static str; 2] = [
+    "...../.xxx./xxoxx/xxxxx/.....",
+    "...../xxxxx/xxoxx/.xxx./.....",
+];
+const TRIANGLE_ADJACENT_MASKS: [&'static str; 2] = [".../xox/.x.", ".x./xox/..."];
+const KNIGHT_MASKS: [&'static str; 1] = [".x.x./x...x/..o../x...x/.x.x."];
+
+impl Tiling {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Tiling::Square => "square",
+            Tiling::Hex => "hex",
+            Tiling::Triangle => "triangle",
+        }
+    }
+
+    /// Every cell touching a cell, the usual thing for numbers to count.
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        match *self {
+            Tiling::Square => Neighbourhood::builtin(Parity::None, &SQUARE_MASKS),
+            Tiling::Hex => Neighbourhood::builtin(Parity::Row, &HEX_MASKS),
+            Tiling::Triangle => Neighbourhood::builtin(Parity::Checker, &TRIANGLE_MASKS),
+        }
+    }
+
+    /// The cells sharing an edge with a cell.
+    pub fn adjacency(&self) -> Neighbourhood {
+        match *self {
+            Tiling::Square => Neighbourhood::builtin(Parity::None, &SQUARE_ADJACENT_MASKS),
+            Tiling::Hex => Neighbourhood::builtin(Parity::Row, &HEX_MASKS),
+            Tiling::Triangle => Neighbourhood::builtin(Parity::Checker, &TRIANGLE_ADJACENT_MASKS),
+        }
+    }
+}
+
+/// Which of a neighbourhood's two masks a cell uses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Parity {
+    /// Every cell uses the first.
+    None,
+    /// Odd rows use the second.
+    Row,
+    /// Cells whose column and row add up to an odd number use the second.
+    Checker,
+}
+
+impl Default for Parity {
+    fn default() -> Parity {
+        Parity::None
+    }
+}
+
+/// How far from the cell a mask may reach.
+const MASK_RADIUS: isize = 3;
+const MASK_SIDE: isize = 2 * MASK_RADIUS + 1;
+
+/// The cells around a cell, relative to it. Each mask is a 7 by 7 grid centred on the cell, one
+/// bit per cell in row-major order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Neighbourhood {
+    pub parity: Parity,
+    masks: [u64; 2],
+}
+
+impl Neighbourhood {
+    /// A neighbourhood in which every cell uses the same mask, written as rows of `.` and `x`
+    /// around an `o` in the middle, separated by `/`. At most seven rows of seven.
+    pub fn parse(text: &str) -> Result<Neighbourhood, String> {
+        let mask = try!(parse_mask(text));
+        if mask == 0 {
+            return Err("the mask has no neighbours; mark them with x".to_owned());
+        }
+        Ok(Neighbourhood {
+            parity: Parity::None,
+            masks: [mask, mask],
+        })
+    }
+
+    /// Numbers count the mines a knight's move away.
+    pub fn knight() -> Neighbourhood {
+        Neighbourhood::builtin(Parity::None, &KNIGHT_MASKS)
+    }
+
+    fn builtin(parity: Parity, masks: &[&str]) -> Neighbourhood {
+        let first = parse_mask(masks[0]).unwrap();
+        Neighbourhood {
+            parity: parity,
+            masks: [first, masks.get(1).map_or(first, |mask| parse_mask(mask).unwrap())],
+        }
+    }
+
+    /// The column and row offsets of `(column, row)`'s neighbours.
+    pub fn offsets(&self, column: usize, row: usize) -> Vec<(isize, isize)> {
+        let second = match self.parity {
+            Parity::None => false,
+            Parity::Row => row % 2 == 1,
+            Parity::Checker => (column + row) % 2 == 1,
+        };
+        self.mask_offsets(second as usize)
+    }
+
+    /// The offsets marked in the first or second mask.
+    fn mask_offsets(&self, mask: usize) -> Vec<(isize, isize)> {
+        let mask = self.masks[mask];
+        (0..MASK_SIDE * MASK_SIDE).filter(|&bit| mask & (1 << bit) != 0).map(|bit| {
+            (bit % MASK_SIDE - MASK_RADIUS, bit / MASK_SIDE - MASK_RADIUS)
+        }).collect()
+    }
+
+    /// How far the furthest neighbour is from the cell, in rows or columns.
+    pub fn radius(&self) -> isize {
+        let mut radius = 0;
+        for bit in 0..MASK_SIDE * MASK_SIDE {
+            if (self.masks[0] | self.masks[1]) & (1 << bit) != 0 {
+                let (dc, dr) = (bit % MASK_SIDE - MASK_RADIUS, bit / MASK_SIDE - MASK_RADIUS);
+                radius = *[radius, dc.abs(), dr.abs()].iter().max().unwrap();
+            }
+        }
+        radius
+    }
+}
+
+/// Writes the first mask in the form `parse` reads, as small as it fits.
+impl fmt::Display for Neighbourhood {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let radius = self.radius();
+        let rows: Vec<String> = (-radius..radius + 1).map(|dr| {
+            (-radius..radius + 1).map(|dc| {
+                let bit = (dr + MASK_RADIUS) * MASK_SIDE + dc + MASK_RADIUS;
+                if dc == 0 && dr == 0 {
+                    'o'
+                } else if self.masks[0] & (1 << bit) != 0 {
+                    'x'
+                } else {
+                    '.'
+                }
+            }).collect()
+        }).collect();
+        write!(f, "{}", rows.join("/"))
+    }
+}
+
+fn parse_mask(text: &str) -> Result<u64, String> {
+    let rows: Vec<&str> = text.split('/').collect();
+    let side = rows.len();
+    if side % 2 == 0 || side > MASK_SIDE as usize || rows.iter().any(|row| row.len() != side) {
+        return Err(format!("a mask must be an odd number of rows as long as it is tall, at most \
+                            {0} by {0}: {1}", MASK_SIDE, text));
+    }
+    let radius = (side / 2) as isize;
+    let mut mask = 0;
+    for (r, row) in rows.iter().enumerate() {
+        for (c, mark) in row.chars().enumerate() {
+            let (dc, dr) = (c as isize - radius, r as isize - radius);
+            match (mark, dc == 0 && dr == 0) {
+                ('o', true) => (),
+                ('x', false) => mask |= 1 << ((dr + MASK_RADIUS) * MASK_SIDE + dc + MASK_RADIUS),
+                ('.', false) => (),
+                _ => return Err(format!("a mask has `o` in the middle and only `x` and `.` \
+                                         around it: {}", text)),
+            }
+        }
+    }
+    Ok(mask)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Topology {
+    pub tiling: Tiling,
+    /// The cells a cell's number counts.
+    pub neighbourhood: Neighbourhood,
+    /// Whether neighbours wrap around the edges of the board.
+    pub wrap: bool,
+}
+
+impl Default for Topology {
+    fn default() -> Topology {
+        Topology::new(Tiling::Square, false)
+    }
+}
+
+/// Suffix marking a wrapping topology in its name.
+const TORUS_SUFFIX: &'static str = "-torus";
+/// Prefix of a square grid with a custom neighbourhood.
+const MASK_PREFIX: &'static str = "mask:";
+
+impl Topology {
+    /// A tiling whose numbers count every touching cell.
+    pub fn new(tiling: Tiling, wrap: bool) -> Topology {
+        Topology::with_neighbourhood(tiling, tiling.neighbourhood(), wrap)
+    }
+
+    pub fn with_neighbourhood(tiling: Tiling, neighbourhood: Neighbourhood,
+                              wrap: bool) -> Topology {
+        Topology {
+            tiling: tiling,
+            neighbourhood: neighbourhood,
+            wrap: wrap,
+        }
+    }
+
+    /// The topology's name, with `-torus` appended if it wraps: `square`, `hex-torus`, `knight`,
+    /// `mask:.x./xox/.x.`, ...
+    pub fn name(&self) -> String {
+        let name = if self.neighbourhood == self.tiling.neighbourhood() {
+            self.tiling.name().to_owned()
+        } else if self.neighbourhood == Neighbourhood::knight() {
+            "knight".to_owned()
+        } else {
+            format!("{}{}", MASK_PREFIX, self.neighbourhood)
+        };
+        if self.wrap {
+            format!("{}{}", name, TORUS_SUFFIX)
+        } else {
+            name
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Topology, String> {
+        let wrap = name.ends_with(TORUS_SUFFIX);
+        let base = if wrap { &name[..name.len() - TORUS_SUFFIX.len()] } else { name };
+        match base {
+            "square" => Ok(Topology::new(Tiling::Square, wrap)),
+            "hex" => Ok(Topology::new(Tiling::Hex, wrap)),
+            "triangle" => Ok(Topology::new(Tiling::Triangle, wrap)),
+            "knight" => Ok(Topology::with_neighbourhood(Tiling::Square, Neighbourhood::knight(),
+                                                        wrap)),
+            _ if base.starts_with(MASK_PREFIX) => {
+                let neighbourhood = try!(Neighbourhood::parse(&base[MASK_PREFIX.len()..]));
+                Ok(Topology::with_neighbourhood(Tiling::Square, neighbourhood, wrap))
+            },
+            _ => Err(format!("unknown topology: {}", name)),
+        }
+    }
+
+    /// Check that a `width` by `height` board can be laid out with this topology.
+    ///
+    /// A hex torus needs an even number of rows and a triangle torus an even number of rows and
+    /// columns: the masks alternate, and the last row or column must alternate with the first one
+    /// it wraps onto.
+    pub fn check(&self, width: usize, height: usize) -> Result<(), String> {
+        if width == 0 || height == 0 {
+            return Err("the board must have at least one row and column".to_owned());
+        }
+        if self.wrap {
+            let parity = self.neighbourhood.parity;
+            if parity != Parity::None && height % 2 == 1 {
+                return Err(format!("a {} torus needs an even number of rows, not {}",
+                                   self.tiling.name(), height));
+            }
+            if parity == Parity::Checker && width % 2 == 1 {
+                return Err(format!("a {} torus needs an even number of columns, not {}",
+                                   self.tiling.name(), width));
+            }
+        }
+        Ok(())
+    }
+
+    /// The cell one step from `coord` in `direction` within its layer, or `None` if the tiling
+    /// has no cell that way or the step leaves a board that doesn't wrap.
+    pub fn step(&self, coord: Coord, direction: Direction, size: Size) -> Option<Coord> {
+        direction.offset(self.tiling, coord.column, coord.row)
+            .and_then(|offset| self.shift(coord, offset, size))
+    }
+
+    fn shift(&self, coord: Coord, (dc, dr): (isize, isize), size: Size) -> Option<Coord> {
+        let column = coord.column as isize + dc;
+        let row = coord.row as isize + dr;
+        let (width, height) = (size.width as isize, size.height as isize);
+        if self.wrap {
+            Some(Coord::new(((column % width + width) % width) as usize,
+                            ((row % height + height) % height) as usize, coord.layer))
+        } else if column < 0 || row < 0 || column >= width || row >= height {
+            None
+        } else {
+            Some(Coord::new(column as usize, row as usize, coord.layer))
+        }
+    }
+
+    /// The neighbours of every cell on a board of `size`, ready for looking up.
+    pub fn stencil(&self, size: Size) -> Stencil {
+        Stencil::new(self.neighbourhood, self.wrap, size, true)
+    }
+
+    /// The cells sharing an edge (or, between layers, a face) with each cell. This depends only
+    /// on the tiling, not on what numbers count.
+    pub fn adjacency(&self, size: Size) -> Stencil {
+        Stencil::new(self.tiling.adjacency(), self.wrap, size, false)
+    }
+}
+
+/// A neighbourhood laid over a board of one size. Neighbours are found by adding precomputed
+/// offsets, and only cells near the edges need checking or wrapping.
+#[derive(Clone, Debug, Default)]
+pub struct Stencil {
+    size: Size,
+    wrap: bool,
+    parity: Parity,
+    /// Column, row and layer offsets, one list per mask.
+    offsets: [Vec<(isize, isize, isize)>; 2],
+    /// How many columns or rows the offsets reach.
+    radius: usize,
+    /// Whether the board is a torus too small for the neighbourhood, where offsets can land on
+    /// the cell itself or on the same neighbour twice.
+    crowded: bool,
+}
+
+impl Stencil {
+    /// With `diagonal_layers` a cell neighbours its neighbours' cells on the layers above and
+    /// below, as well as its own; without, only its own.
+    fn new(neighbourhood: Neighbourhood, wrap: bool, size: Size, diagonal_layers: bool) -> Stencil {
+        let layers: &[isize] = if size.depth > 1 { &[-1, 0, 1] } else { &[0] };
+        let offsets = [0, 1].iter().map(|&parity| {
+            let planar = neighbourhood.mask_offsets(parity);
+            let mut offsets = vec![];
+            for &dl in layers {
+                if dl != 0 {
+                    offsets.push((0, 0, dl));
+                }
+                if dl == 0 || diagonal_layers {
+                    offsets.extend(planar.iter().map(|&(dc, dr)| (dc, dr, dl)));
+                }
+            }
+            offsets
+        }).collect::<Vec<_>>();
+        let radius = neighbourhood.radius() as usize;
+        let side = 2 * radius + 1;
+        Stencil {
+            size: size,
+            wrap: wrap,
+            parity: neighbourhood.parity,
+            offsets: [offsets[0].clone(), offsets[1].clone()],
+            radius: radius,
+            crowded: wrap && (size.width < side || size.height < side),
+        }
+    }
+
+    pub fn neighbours(&self, coord: Coord) -> Neighbours {
+        let second = match self.parity {
+            Parity::None => false,
+            Parity::Row => coord.row % 2 == 1,
+            Parity::Checker => (coord.column + coord.row) % 2 == 1,
+        };
+        let (radius, size) = (self.radius, self.size);
+        Neighbours {
+            stencil: self,
+            coord: coord,
+            offsets: self.offsets[second as usize].iter(),
+            inside: coord.column >= radius && coord.column + radius < size.width &&
+                    coord.row >= radius && coord.row + radius < size.height,
+            seen: vec![],
+        }
+    }
+}
+
+/// The neighbours of a cell, from `Stencil::neighbours`.
+#[derive(Clone, Debug)]
+pub struct Neighbours<'a> {
+    stencil: &'a Stencil,
+    coord: Coord,
+    offsets: slice::Iter<'a, (isize, isize, isize)>,
+    /// Whether every offset stays within the board's columns and rows.
+    inside: bool,
+    /// Neighbours given out so far, only kept on crowded tori.
+    seen: Vec<Coord>,
+}
+
+impl<'a> Iterator for Neighbours<'a> {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Coord> {
+        let stencil = self.stencil;
+        let (width, height) = (stencil.size.width as isize, stencil.size.height as isize);
+        while let Some(&(dc, dr, dl)) = self.offsets.next() {
+            let layer = self.coord.layer as isize + dl;
+            if layer < 0 || layer >= stencil.size.depth as isize {
+                continue;
+            }
+            let column = self.coord.column as isize + dc;
+            let row = self.coord.row as isize + dr;
+            if self.inside {
+                return Some(Coord::new(column as usize, row as usize, layer as usize));
+            }
+            let neighbour = if stencil.wrap {
+                Coord::new(((column % width + width) % width) as usize,
+                           ((row % height + height) % height) as usize, layer as usize)
+            } else if column < 0 || row < 0 || column >= width || row >= height {
+                continue;
+            } else {
+                Coord::new(column as usize, row as usize, layer as usize)
+            };
+            if stencil.crowded {
+                if neighbour == self.coord || self.seen.contains(&neighbour) {
+                    continue;
+                }
+                self.seen.push(neighbour);
+            }
+            return Some(neighbour);
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.offsets.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use coord::{Coord, Size};
+    use super::{DIRECTIONS, Tiling, Topology};
+
+    /// The distinct cells one step from `coord`, and how many neighbours its number counts.
+    fn counts(topology: Topology, size: Size, coord: Coord) -> (usize, usize) {
+        let mut steps: Vec<Coord> = DIRECTIONS.iter()
+            .filter_map(|&direction| topology.step(coord, direction, size))
+            .collect();
+        let stepped = steps.len();
+        steps.sort_by_key(|coord| (coord.row, coord.column));
+        steps.dedup();
+        assert_eq!(steps.len(), stepped, "{:?} steps onto a cell twice", coord);
+        (stepped, topology.stencil(size).neighbours(coord).count())
+    }
+
+    #[test]
+    fn hex_neighbours() {
+        let size = Size::new(6, 6, 1);
+        let hex = Topology::new(Tiling::Hex, false);
+        // An even row, an odd row, a corner and the odd end of a row.
+        assert_eq!(counts(hex, size, Coord::flat(2, 2)), (6, 6));
+        assert_eq!(counts(hex, size, Coord::flat(2, 3)), (6, 6));
+        assert_eq!(counts(hex, size, Coord::flat(0, 0)), (2, 2));
+        assert_eq!(counts(hex, size, Coord::flat(5, 1)), (3, 3));
+        let torus = Topology::new(Tiling::Hex, true);
+        for coord in size.coords() {
+            assert_eq!(counts(torus, size, coord), (6, 6));
+        }
+    }
+
+    #[test]
+    fn triangle_neighbours() {
+        let size = Size::new(6, 6, 1);
+        let triangle = Topology::new(Tiling::Triangle, false);
+        // Pointing up, pointing down, and both in the top corner.
+        assert_eq!(counts(triangle, size, Coord::flat(2, 2)), (3, 12));
+        assert_eq!(counts(triangle, size, Coord::flat(3, 2)), (3, 12));
+        assert_eq!(counts(triangle, size, Coord::flat(0, 0)), (2, 5));
+        assert_eq!(counts(triangle, size, Coord::flat(1, 0)), (2, 6));
+        let torus = Topology::new(Tiling::Triangle, true);
+        for coord in size.coords() {
+            assert_eq!(counts(torus, size, coord), (3, 12));
+        }
+    }
+
+    #[test]
+    fn steps_are_adjacent() {
+        let size = Size::new(6, 6, 1);
+        for &tiling in [Tiling::Square, Tiling::Hex, Tiling::Triangle].iter() {
+            let topology = Topology::new(tiling, false);
+            let neighbours = topology.stencil(size);
+            for coord in size.coords() {
+                for step in DIRECTIONS.iter().filter_map(|&d| topology.step(coord, d, size)) {
+                    assert!(neighbours.neighbours(coord).any(|n| n == step),
+                            "{:?} steps from {:?} to {:?}", tiling, coord, step);
+                }
+            }
+        }
+    }
+}
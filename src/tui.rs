@@ -0,0 +1,265 @@
+//! A terminal frontend. The board is printed after every command and moves are typed in, so it
+//! works in any terminal without raw mode.
+//!
+//! On layered boards the current layer is printed between the layers above and below it, which
+//! is where the rest of each number's mines are hidden.
+//!
+//! Endless worlds are played through a window onto them, which can be moved around.
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use ansi_term::Style;
+
+use minesweeper::board::Board;
+use minesweeper::coord::Coord;
+use minesweeper::game::{millis, Game, Status};
+use minesweeper::render::{self, Renderer};
+use minesweeper::settings::Settings;
+use minesweeper::stats::{self, Record, Stats};
+use minesweeper::world::{Point, World};
+
+const HELP: &'static str = "\
+Commands (columns and rows count from 1):
+  o COLUMN ROW    open a cell
+  f COLUMN ROW    flag or unflag a cell
+  c COLUMN ROW    chord: open around a satisfied number
+  u / d           show the layer above / below
+  p               pause or resume, hiding the board
+  n               new game
+  q               quit
+  ?               this help";
+
+/// The terminal frontend's state.
+struct Tui {
+    game: Game,
+    stats: Stats,
+    /// The player's preferences, which every game dealt here plays by, as in the window.
+    settings: Settings,
+    recorded: bool,
+    /// The layer being shown and played on.
+    layer: usize,
+}
+
+/// Play `game` in the terminal, and new games like it, until the player quits or input ends.
+pub fn run(mut game: Game) -> io::Result<()> {
+    let settings = Settings::load_or_warn();
+    game.first_click = settings.first_click;
+    game.question_marks = settings.question_marks;
+    let mut tui = Tui {
+        game: game,
+        stats: Stats::load().unwrap_or_else(|e| {
+            println!("Couldn't read statistics, starting afresh: {}", e);
+            Default::default()
+        }),
+        settings: settings,
+        recorded: false,
+        layer: 0,
+    };
+
+    println!("{}", HELP);
+    let stdin = io::stdin();
+    loop {
+        try!(tui.print());
+        print!("> ");
+        try!(io::stdout().flush());
+
+        let mut line = String::new();
+        if try!(stdin.lock().read_line(&mut line)) == 0 {
+            return Ok(());
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.first().map(|word| *word) {
+            Some("q") => return Ok(()),
+            Some("?") | Some("h") => println!("{}", HELP),
+            Some("p") => if tui.game.is_paused() {
+                tui.game.resume();
+            } else {
+                tui.game.pause();
+            },
+            Some("n") => {
+                tui.game = Game::random(tui.game.preset, tui.game.topology);
+                tui.game.first_click = tui.settings.first_click;
+                tui.game.question_marks = tui.settings.question_marks;
+                tui.recorded = false;
+                tui.layer = 0;
+            },
+            Some(_) if tui.game.is_paused() => println!("Paused. Type p to resume."),
+            Some("u") => if tui.layer > 0 {
+                tui.layer -= 1;
+            },
+            Some("d") => if tui.layer + 1 < tui.game.preset.depth() {
+                tui.layer += 1;
+            },
+            Some(action @ "o") | Some(action @ "f") | Some(action @ "c") => {
+                match parse_cell(&words[1..], &tui.game.board(), tui.layer) {
+                    Some(coord) => {
+                        match action {
+                            "o" => tui.game.open(coord),
+                            "f" => tui.game.toggle_flag(coord),
+                            _ => tui.game.chord(coord),
+                        }
+                        tui.record_if_over();
+                    },
+                    None => println!("Expected a column and a row on the board, e.g. `{} 3 4`",
+                                     action),
+                }
+            },
+            Some(_) => println!("Unknown command. Type ? for help."),
+            None => (),
+        }
+    }
+}
+
+/// Parse 1-based `COLUMN ROW` into the coordinates of a cell on `layer` of the board.
+fn parse_cell(words: &[&str], board: &Board, layer: usize) -> Option<Coord> {
+    if words.len() != 2 {
+        return None;
+    }
+    match (words[0].parse::<usize>(), words[1].parse::<usize>()) {
+        (Ok(column), Ok(row)) if column >= 1 && row >= 1 => {
+            let coord = Coord::new(column - 1, row - 1, layer);
+            if board.size().contains(coord) { Some(coord) } else { None }
+        },
+        _ => None,
+    }
+}
+
+impl Tui {
+    fn record_if_over(&mut self) {
+        if self.recorded {
+            return;
+        }
+        if let Some(record) = Record::from_game(&self.game) {
+            self.recorded = true;
+            if let Err(e) = self.stats.push(record) {
+                println!("Couldn't save statistics: {}", e);
+            }
+        }
+    }
+
+    fn print(&self) -> io::Result<()> {
+        let board = self.game.board();
+        let status = match self.game.status {
+            _ if self.game.is_paused() => "Paused",
+            Status::Playing => "Minesweeper",
+            Status::Failed => "EXPLODE!",
+            Status::Finished => "Cleared!",
+        };
+        println!("");
+        println!("{}  {}  mines left: {}", Style::new().bold().paint(status),
+                 stats::format_time(millis(self.game.elapsed())), board.mines_left());
+        // The board stays hidden until the game is resumed.
+        if self.game.is_paused() {
+            return Ok(());
+        }
+
+        // The current layer, flanked by its neighbours on layered boards.
+        let mut layers = vec![];
+        if self.layer > 0 {
+            layers.push(self.layer - 1);
+        }
+        layers.push(self.layer);
+        if self.layer + 1 < board.depth {
+            layers.push(self.layer + 1);
+        }
+
+        let renderer = Renderer::new().rulers(true).colour(true);
+        let blocks: Vec<Vec<String>> = layers.iter().map(|&layer| {
+            let current = layer == self.layer;
+            let mut block = renderer.dimmed(!current).player_layer(&board, layer);
+            if board.depth > 1 {
+                block.insert(0, format!("layer {}/{}{}", layer + 1, board.depth,
+                                        if current { " *" } else { "" }));
+            }
+            block
+        }).collect();
+        for line in render::side_by_side(&blocks, "   ") {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+}
+
+
+const WORLD_HELP: &'static str = "\
+Commands (columns and rows count from 1, within the view):
+  o COLUMN ROW    open a cell
+  f COLUMN ROW    flag or unflag a cell
+  c COLUMN ROW    chord: open around a satisfied number, or carry on an opening
+                  that was too big to open at once
+  w / a / s / d   move the view up / left / down / right
+  save            save the world
+  q               save and quit
+  ?               this help";
+
+/// Cells shown of an endless world.
+const VIEW_WIDTH: usize = 30;
+const VIEW_HEIGHT: usize = 16;
+/// How far the view moves at a time. Even, so hex rows keep their offset.
+const VIEW_STEP: i64 = 8;
+
+/// Explore an endless world in the terminal, saving it to `path` on the way out.
+pub fn run_world(mut world: World, path: &Path) -> io::Result<()> {
+    // The view starts with the safe starting cell in its middle.
+    let mut from = Point::new(-(VIEW_WIDTH as i64) / 2 & !1, -(VIEW_HEIGHT as i64) / 2 & !1);
+
+    println!("{}", WORLD_HELP);
+    let stdin = io::stdin();
+    loop {
+        print_world(&mut world, from);
+        print!("> ");
+        try!(io::stdout().flush());
+
+        let mut line = String::new();
+        if try!(stdin.lock().read_line(&mut line)) == 0 {
+            return world.save(path);
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.first().map(|word| *word) {
+            Some("q") => return world.save(path),
+            Some("?") | Some("h") => println!("{}", WORLD_HELP),
+            Some("save") => match world.save(path) {
+                Ok(()) => println!("Saved to {}", path.display()),
+                Err(e) => println!("Couldn't save the world: {}", e),
+            },
+            Some("w") => from.row -= VIEW_STEP,
+            Some("a") => from.column -= VIEW_STEP,
+            Some("s") => from.row += VIEW_STEP,
+            Some("d") => from.column += VIEW_STEP,
+            Some(action @ "o") | Some(action @ "f") | Some(action @ "c") => {
+                let board = world.view(from, VIEW_WIDTH, VIEW_HEIGHT);
+                match parse_cell(&words[1..], &board, 0) {
+                    Some(coord) => {
+                        let point = Point::new(from.column + coord.column as i64,
+                                               from.row + coord.row as i64);
+                        match action {
+                            "o" => { world.open(point); },
+                            "f" => world.toggle_flag(point),
+                            _ => { world.chord(point); },
+                        }
+                        if action != "f" && world.cut_short {
+                            println!("That opening was too big to open at once. Chord one of \
+                                      the zeros at its edge to open more of it.");
+                        }
+                    },
+                    None => println!("Expected a column and a row in the view, e.g. `{} 3 4`",
+                                     action),
+                }
+            },
+            Some(_) => println!("Unknown command. Type ? for help."),
+            None => (),
+        }
+    }
+}
+
+fn print_world(world: &mut World, from: Point) {
+    let status = if world.exploded { "EXPLODE!" } else { "Endless Minesweeper" };
+    println!("");
+    println!("{}  opened: {}  view from {},{}", Style::new().bold().paint(status),
+             world.opened(), from.column, from.row);
+    let board = world.view(from, VIEW_WIDTH, VIEW_HEIGHT);
+    for line in Renderer::new().rulers(true).colour(true).player_layer(&board, 0) {
+        println!("{}", line);
+    }
+}
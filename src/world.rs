@@ -0,0 +1,351 @@
+//! Endless Minesweeper: a board without edges, generated as it is explored.
+//!
+//! The plane is cut into square chunks. A chunk's mines depend only on the world seed and the
+//! chunk's position, so they are drawn the first time anything looks at the chunk and come out
+//! the same every time; numbers next to a chunk's border look at the neighbouring chunk's mines
+//! the same way. The starting cell at `(0, 0)` and its neighbours never hold a mine.
+//!
+//! Only what the player has done is saved: the seed, and the opened and flagged cells of every
+//! chunk that has any.
+//!
+//! Saved worlds are text. The first line is `minesweeper-world 1`, the second holds the seed,
+//! mine density, topology and whether a mine went off, and every further line is a chunk: its
+//! column and row (in chunks) and one character per cell, row by row: `#` untouched, `o` opened
+//! and `f` flagged.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+use board::{Board, Tile};
+use map::Interaction;
+use paths;
+use topology::Topology;
+
+/// Cells along each side of a chunk.
+pub const CHUNK: i64 = 32;
+const CELLS: usize = (CHUNK * CHUNK) as usize;
+/// The most cells a single opening floods, so a sparse world can't flood forever. A flood cut
+/// short leaves opened zeros with closed neighbours, which chording opens further.
+const MAX_FLOOD: usize = 250_000;
+const MAGIC: &'static str = "minesweeper-world 1";
+
+/// A cell of the world. Columns grow to the right and rows downwards, from `(0, 0)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub column: i64,
+    pub row: i64,
+}
+
+impl Point {
+    pub fn new(column: i64, row: i64) -> Point {
+        Point {
+            column: column,
+            row: row,
+        }
+    }
+
+    /// The chunk holding the point, and the point's position in it.
+    fn chunk(&self) -> ((i64, i64), usize) {
+        let (cx, cy) = (floor_div(self.column, CHUNK), floor_div(self.row, CHUNK));
+        let (x, y) = (self.column - cx * CHUNK, self.row - cy * CHUNK);
+        ((cx, cy), (y * CHUNK + x) as usize)
+    }
+}
+
+fn floor_div(value: i64, by: i64) -> i64 {
+    let quotient = value / by;
+    if value % by < 0 { quotient - 1 } else { quotient }
+}
+
+struct Chunk {
+    mines: Vec<bool>,
+    state: Vec<Interaction>,
+}
+
+pub struct World {
+    pub seed: u64,
+    /// The chance of any cell holding a mine.
+    pub density: f64,
+    /// Which cells neighbour each other. Worlds don't wrap.
+    pub topology: Topology,
+    /// Whether the player has opened a mine.
+    pub exploded: bool,
+    /// Whether the last open or chord stopped flooding at `MAX_FLOOD`, for the player to be told.
+    pub cut_short: bool,
+    chunks: HashMap<(i64, i64), Chunk>,
+    /// The neighbours' offsets for cells with an even or odd column and an even or odd row.
+    offsets: Vec<Vec<(isize, isize)>>,
+}
+
+impl World {
+    /// A fresh world. The density must be between 0.1 and 0.5: sparser worlds have endless
+    /// openings, denser ones can't be solved.
+    pub fn new(seed: u64, density: f64, topology: Topology) -> Result<World, String> {
+        if !(density >= 0.1 && density <= 0.5) {
+            return Err(format!("the mine density must be between 0.1 and 0.5, not {}", density));
+        }
+        if topology.wrap {
+            return Err("an endless world can't wrap around".to_owned());
+        }
+        let offsets = [(0, 0), (0, 1), (1, 0), (1, 1)].iter().map(|&(column, row)| {
+            topology.neighbourhood.offsets(column, row)
+        }).collect();
+        Ok(World {
+            seed: seed,
+            density: density,
+            topology: topology,
+            exploded: false,
+            cut_short: false,
+            chunks: HashMap::new(),
+            offsets: offsets,
+        })
+    }
+
+    /// The cells whose mines `point`'s number counts.
+    pub fn neighbours(&self, point: Point) -> Vec<Point> {
+        let parity = ((point.column & 1) * 2 + (point.row & 1)) as usize;
+        self.offsets[parity].iter().map(|&(dc, dr)| {
+            Point::new(point.column + dc as i64, point.row + dr as i64)
+        }).collect()
+    }
+
+    fn chunk(&mut self, key: (i64, i64)) -> &mut Chunk {
+        if !self.chunks.contains_key(&key) {
+            let chunk = self.generate(key);
+            self.chunks.insert(key, chunk);
+        }
+        self.chunks.get_mut(&key).unwrap()
+    }
+
+    /// Draw a chunk's mines from the world seed and the chunk's position.
+    fn generate(&self, (cx, cy): (i64, i64)) -> Chunk {
+        let mut hash = self.seed ^ 0x9e37_79b9_7f4a_7c15;
+        for &value in [cx as u64, cy as u64].iter() {
+            hash = (hash ^ value).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            hash ^= hash >> 31;
+        }
+        // XorShift refuses an all-zero seed, so the last word is never zero.
+        let mut rng = XorShiftRng::from_seed([hash as u32, (hash >> 32) as u32, cx as u32,
+                                              cy as u32 | 1]);
+        let mut mines: Vec<bool> = (0..CELLS).map(|_| rng.next_f64() < self.density).collect();
+
+        let start = Point::new(0, 0);
+        let mut safe = self.neighbours(start);
+        safe.push(start);
+        for point in safe {
+            let (key, index) = point.chunk();
+            if key == (cx, cy) {
+                mines[index] = false;
+            }
+        }
+        Chunk {
+            mines: mines,
+            state: vec![Interaction::Undiscovered; CELLS],
+        }
+    }
+
+    fn is_mine(&mut self, point: Point) -> bool {
+        let (key, index) = point.chunk();
+        self.chunk(key).mines[index]
+    }
+
+    pub fn interaction(&mut self, point: Point) -> Interaction {
+        let (key, index) = point.chunk();
+        self.chunk(key).state[index]
+    }
+
+    fn set_interaction(&mut self, point: Point, interaction: Interaction) {
+        let (key, index) = point.chunk();
+        self.chunk(key).state[index] = interaction;
+    }
+
+    /// How many of `point`'s neighbours hold a mine.
+    pub fn nearby(&mut self, point: Point) -> usize {
+        self.neighbours(point).into_iter().filter(|&neighbour| self.is_mine(neighbour)).count()
+    }
+
+    /// What the player sees at `point`.
+    pub fn tile(&mut self, point: Point) -> Tile {
+        match self.interaction(point) {
+            Interaction::Undiscovered => Tile::Unknown,
+            Interaction::Flagged => Tile::Flagged,
+            Interaction::Questioned => Tile::Questioned,
+            Interaction::Opened if self.is_mine(point) => Tile::Mine,
+            Interaction::Opened => Tile::Number(self.nearby(point)),
+        }
+    }
+
+    /// Open a cell, flooding outwards from cells without mines nearby. Returns `true` if the
+    /// cell held a mine.
+    pub fn open(&mut self, point: Point) -> bool {
+        self.cut_short = false;
+        if self.exploded || self.interaction(point) != Interaction::Undiscovered {
+            return false;
+        }
+        if self.is_mine(point) {
+            self.set_interaction(point, Interaction::Opened);
+            self.exploded = true;
+            return true;
+        }
+        let mut pending = vec![point];
+        let mut flooded = 0;
+        while let Some(point) = pending.pop() {
+            if flooded == MAX_FLOOD {
+                self.cut_short = true;
+                break;
+            }
+            if self.is_mine(point) || self.interaction(point) != Interaction::Undiscovered {
+                continue;
+            }
+            self.set_interaction(point, Interaction::Opened);
+            flooded += 1;
+            if self.nearby(point) == 0 {
+                pending.extend(self.neighbours(point));
+            }
+        }
+        false
+    }
+
+    /// Open the unflagged neighbours of an opened number whose flags are all placed. On a zero
+    /// left by a flood cut short, that carries the flood on.
+    pub fn chord(&mut self, point: Point) -> bool {
+        self.cut_short = false;
+        if self.interaction(point) != Interaction::Opened {
+            return false;
+        }
+        let neighbours = self.neighbours(point);
+        let flagged = neighbours.iter().filter(|&&neighbour| {
+            self.interaction(neighbour) == Interaction::Flagged
+        }).count();
+        if flagged != self.nearby(point) {
+            return false;
+        }
+        let (mut exploded, mut cut_short) = (false, false);
+        for neighbour in neighbours {
+            exploded |= self.open(neighbour);
+            cut_short |= self.cut_short;
+        }
+        self.cut_short = cut_short;
+        exploded
+    }
+
+    pub fn toggle_flag(&mut self, point: Point) {
+        if self.exploded {
+            return;
+        }
+        let interaction = match self.interaction(point) {
+            Interaction::Undiscovered => Interaction::Flagged,
+            Interaction::Flagged | Interaction::Questioned => Interaction::Undiscovered,
+            Interaction::Opened => Interaction::Opened,
+        };
+        self.set_interaction(point, interaction);
+    }
+
+    /// How many cells have been opened, the world's score.
+    pub fn opened(&self) -> usize {
+        self.chunks.values().map(|chunk| {
+            chunk.state.iter().filter(|&&state| state == Interaction::Opened).count()
+        }).sum()
+    }
+
+    /// The `width` by `height` window of the world whose top left cell is `from`, as the player
+    /// sees it. After an explosion the mines in the window are shown. Start the window on an
+    /// even column and row, or hex and triangle boards are drawn with the wrong cells offset.
+    pub fn view(&mut self, from: Point, width: usize, height: usize) -> Board {
+        let mut tiles = vec![];
+        for row in 0..height as i64 {
+            for column in 0..width as i64 {
+                let point = Point::new(from.column + column, from.row + row);
+                let tile = match self.tile(point) {
+                    Tile::Unknown if self.exploded && self.is_mine(point) => Tile::Mine,
+                    tile => tile,
+                };
+                tiles.push(tile);
+            }
+        }
+        Board::from_tiles(width, height, self.topology, tiles)
+    }
+
+    /// Where the world is kept between sessions, next to the statistics.
+    pub fn default_path() -> Option<PathBuf> {
+        paths::data_dir().map(|dir| dir.join("world.txt"))
+    }
+
+    /// Load a saved world.
+    pub fn load(path: &Path) -> io::Result<World> {
+        let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, what.to_owned());
+        let file = try!(File::open(path));
+        let mut lines = BufReader::new(file).lines();
+
+        if try!(lines.next().unwrap_or(Ok(String::new()))) != MAGIC {
+            return Err(invalid("not a saved world"));
+        }
+        let header = try!(lines.next().unwrap_or(Ok(String::new())));
+        let fields: Vec<&str> = header.split(' ').collect();
+        if fields.len() != 4 {
+            return Err(invalid("malformed world header"));
+        }
+        let seed = try!(fields[0].parse::<u64>().map_err(|_| invalid("malformed seed")));
+        let density = try!(fields[1].parse::<f64>().map_err(|_| invalid("malformed density")));
+        let topology = try!(Topology::from_name(fields[2]).map_err(|e| invalid(&e)));
+        let mut world = try!(World::new(seed, density, topology).map_err(|e| invalid(&e)));
+        world.exploded = fields[3] == "exploded";
+
+        for line in lines {
+            let line = try!(line);
+            let fields: Vec<&str> = line.split(' ').collect();
+            if fields.len() != 3 || fields[2].len() != CELLS {
+                return Err(invalid("malformed chunk"));
+            }
+            let key = match (fields[0].parse::<i64>(), fields[1].parse::<i64>()) {
+                (Ok(cx), Ok(cy)) => (cx, cy),
+                _ => return Err(invalid("malformed chunk position")),
+            };
+            let mut state = vec![];
+            for c in fields[2].chars() {
+                state.push(match c {
+                    '#' => Interaction::Undiscovered,
+                    'o' => Interaction::Opened,
+                    'f' => Interaction::Flagged,
+                    '?' => Interaction::Questioned,
+                    _ => return Err(invalid("malformed chunk")),
+                });
+            }
+            world.chunk(key).state = state;
+        }
+        Ok(world)
+    }
+
+    /// Save the chunks the player has touched.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            try!(fs::create_dir_all(dir));
+        }
+        let mut file = try!(File::create(path));
+        try!(writeln!(file, "{}", MAGIC));
+        try!(writeln!(file, "{} {} {} {}", self.seed, self.density, self.topology.name(),
+                      if self.exploded { "exploded" } else { "playing" }));
+        let mut keys: Vec<&(i64, i64)> = self.chunks.keys().collect();
+        keys.sort();
+        for key in keys {
+            let ref chunk = self.chunks[key];
+            if chunk.state.iter().all(|&state| state == Interaction::Undiscovered) {
+                continue;
+            }
+            let state: String = chunk.state.iter().map(|&state| {
+                match state {
+                    Interaction::Undiscovered => '#',
+                    Interaction::Opened => 'o',
+                    Interaction::Flagged => 'f',
+                    Interaction::Questioned => '?',
+                }
+            }).collect();
+            try!(writeln!(file, "{} {} {}", key.0, key.1, state));
+        }
+        Ok(())
+    }
+}